@@ -1,5 +1,6 @@
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 fn main() {
     let input = include_str!("input.txt");
@@ -8,60 +9,150 @@ fn main() {
     println!("Part 2: {}", part2(input));
 }
 
-fn score_card(input: &str) -> (u32, i32) {
-    let mut wins: Option<i32> = None;
-    let re = Regex::new(r":\s+(.+)\s+\|\s+(.+)").unwrap();
-    let caps = re.captures(input).unwrap();
-    let winners: HashSet<i32> = HashSet::from_iter(
-        caps.get(1)
-            .map_or("", |m| m.as_str())
-            .split_whitespace()
-            .map(|m| m.parse::<i32>().unwrap()),
-    );
-    let picks = caps
-        .get(2)
-        .map_or("", |m| m.as_str())
-        .split_whitespace()
-        .map(|m| m.parse::<i32>().unwrap());
-
-    for pick in picks {
-        if winners.contains(&pick) {
-            wins = Some(wins.unwrap_or(-1) + 1);
+#[derive(Debug, PartialEq)]
+enum CardError {
+    Malformed(String),
+    OutOfSequence { expected: usize, found: usize },
+    DuplicateId(usize),
+    PastLastCard { card: usize, matches: u32 },
+    DuplicateWinner { card: usize, number: u32 },
+    DuplicatePick { card: usize, number: u32 },
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardError::Malformed(line) => write!(f, "malformed card: {line}"),
+            CardError::OutOfSequence { expected, found } => {
+                write!(f, "expected card {expected}, found card {found}")
+            }
+            CardError::DuplicateId(id) => write!(f, "card {id} is listed more than once"),
+            CardError::PastLastCard { card, matches } => {
+                write!(
+                    f,
+                    "card {card} has {matches} matches, which win cards past the last one"
+                )
+            }
+            CardError::DuplicateWinner { card, number } => {
+                write!(
+                    f,
+                    "card {card} lists winning number {number} more than once"
+                )
+            }
+            CardError::DuplicatePick { card, number } => {
+                write!(f, "card {card} lists picked number {number} more than once")
+            }
         }
     }
+}
+
+#[derive(Debug)]
+struct Card {
+    id: usize,
+    winners: HashSet<u32>,
+    picks: Vec<u32>,
+}
 
-    if wins.is_some() {
-        (wins.unwrap() as u32 + 1, 2_i32.pow(wins.unwrap() as u32))
-    } else {
-        (0, 0)
+impl Card {
+    fn parse(re: &Regex, input: &str) -> Result<Card, CardError> {
+        let malformed = || CardError::Malformed(input.to_string());
+        let caps = re.captures(input).ok_or_else(malformed)?;
+        let id = caps[1].parse::<usize>().map_err(|_| malformed())?;
+
+        let numbers = |s: &str| -> Result<Vec<u32>, CardError> {
+            s.split_whitespace()
+                .map(|n| n.parse::<u32>().map_err(|_| malformed()))
+                .collect()
+        };
+
+        // winning numbers are a set, a repeated one is a typo on the card
+        let mut winners = HashSet::new();
+        for number in numbers(&caps[2])? {
+            if !winners.insert(number) {
+                return Err(CardError::DuplicateWinner { card: id, number });
+            }
+        }
+
+        // picks would be counted twice if repeated, so reject them too
+        let picks = numbers(&caps[3])?;
+        let mut seen = HashSet::new();
+        for &number in &picks {
+            if !seen.insert(number) {
+                return Err(CardError::DuplicatePick { card: id, number });
+            }
+        }
+
+        Ok(Card { id, winners, picks })
+    }
+
+    fn matches(&self) -> u32 {
+        self.picks
+            .iter()
+            .filter(|p| self.winners.contains(p))
+            .count() as u32
+    }
+
+    fn score(&self) -> i32 {
+        match self.matches() {
+            0 => 0,
+            n => 2_i32.pow(n - 1),
+        }
     }
 }
 
-fn part1(input: &str) -> i32 {
-    let mut sum: i32 = 0;
+// parse every card and order them by ID, the IDs must run from 1 without gaps
+fn parse_cards(input: &str) -> Result<Vec<Card>, CardError> {
+    let re = Regex::new(r"^Card\s+(\d+):\s+(.+)\s+\|\s+(.+)$").unwrap();
+    let mut cards = input
+        .lines()
+        .map(|line| Card::parse(&re, line))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cards.sort_by_key(|c| c.id);
+    for (i, card) in cards.iter().enumerate() {
+        if i > 0 && card.id == cards[i - 1].id {
+            return Err(CardError::DuplicateId(card.id));
+        }
+        if card.id != i + 1 {
+            return Err(CardError::OutOfSequence {
+                expected: i + 1,
+                found: card.id,
+            });
+        }
+    }
 
-    for line in input.lines() {
-        let (_, score) = score_card(line);
-        sum += score;
+    // a card wins copies of the cards after it, so those cards have to exist
+    for card in &cards {
+        if card.id + card.matches() as usize > cards.len() {
+            return Err(CardError::PastLastCard {
+                card: card.id,
+                matches: card.matches(),
+            });
+        }
     }
 
-    sum
+    Ok(cards)
 }
 
-fn count_cards(cards: &Vec<&str>, visited: &mut HashMap<usize, u32>, card: &str, i: usize) -> u32 {
-    if let Some(count) = visited.get(&i) {
+fn part1(input: &str) -> i32 {
+    let cards = parse_cards(input).unwrap_or_else(|e| panic!("{e}"));
+    cards.iter().map(|c| c.score()).sum()
+}
+
+fn count_cards(cards: &[Card], visited: &mut HashMap<usize, u32>, card: &Card) -> u32 {
+    if let Some(count) = visited.get(&card.id) {
         return *count;
     }
 
-    let (count, _) = score_card(card);
+    let count = card.matches();
     let mut sum = count;
 
-    let start = i + 1;
-    for j in start..start + count as usize {
-        sum += count_cards(cards, visited, cards[j], j);
+    // card IDs start at 1, so the card following this one is at index `id`
+    for next in &cards[card.id..card.id + count as usize] {
+        sum += count_cards(cards, visited, next);
     }
 
-    visited.insert(i, sum);
+    visited.insert(card.id, sum);
 
     sum
 }
@@ -69,12 +160,12 @@ fn count_cards(cards: &Vec<&str>, visited: &mut HashMap<usize, u32>, card: &str,
 fn part2(input: &str) -> u32 {
     let mut sum = 0;
 
-    let cards: Vec<&str> = input.lines().collect();
+    let cards = parse_cards(input).unwrap_or_else(|e| panic!("{e}"));
     let mut visited: HashMap<usize, u32> = HashMap::new();
 
-    for (i, card) in cards.iter().enumerate() {
+    for card in &cards {
         sum += 1;
-        sum += count_cards(&cards, &mut visited, card, i);
+        sum += count_cards(&cards, &mut visited, card);
     }
 
     sum
@@ -105,4 +196,68 @@ mod tests {
         let result = part2(INPUT);
         assert_eq!(result, 30)
     }
+
+    #[test]
+    fn check_card_order_from_ids() {
+        let mut lines: Vec<_> = INPUT.lines().collect();
+        lines.reverse();
+        let input = lines.join("\n");
+        assert_eq!(part1(&input), 13);
+        assert_eq!(part2(&input), 30);
+    }
+
+    #[test]
+    fn check_card_errors() {
+        let skipped = indoc! {"
+            Card 1: 41 48 | 83 86
+            Card 3: 13 32 | 61 30"
+        };
+        assert_eq!(
+            parse_cards(skipped).unwrap_err(),
+            CardError::OutOfSequence {
+                expected: 2,
+                found: 3
+            }
+        );
+
+        let repeated = indoc! {"
+            Card 1: 41 48 | 83 86
+            Card 1: 13 32 | 61 30"
+        };
+        assert_eq!(
+            parse_cards(repeated).unwrap_err(),
+            CardError::DuplicateId(1)
+        );
+
+        // the second card would win a copy of a third card that isn't there
+        let past = indoc! {"
+            Card 1: 41 48 | 83 86
+            Card 2: 13 32 | 13 30"
+        };
+        assert_eq!(
+            parse_cards(past).unwrap_err(),
+            CardError::PastLastCard {
+                card: 2,
+                matches: 1
+            }
+        );
+
+        let winner = "Card 1: 41 48 41 | 83 86";
+        assert_eq!(
+            parse_cards(winner).unwrap_err(),
+            CardError::DuplicateWinner {
+                card: 1,
+                number: 41
+            }
+        );
+
+        let pick = "Card 1: 41 48 | 83 86 83";
+        assert_eq!(
+            parse_cards(pick).unwrap_err(),
+            CardError::DuplicatePick {
+                card: 1,
+                number: 83
+            }
+        );
+    }
 }
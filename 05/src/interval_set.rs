@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+/// A set of values stored as half-open ranges.
+///
+/// The ranges are kept sorted, non-empty and separated by at least one value
+/// that isn't in the set, so two sets with the same members compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    // sort the ranges and merge any that overlap or touch
+    fn normalize(mut ranges: Vec<Range<T>>) -> IntervalSet<T> {
+        ranges.retain(|r| r.start < r.end);
+        ranges.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The smallest value in the set.
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    /// Add a range to the set, merging it with the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+
+        // everything in `first..last` overlaps or touches the new range
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if first < last {
+            range.start.min(self.ranges[first].start)..range.end.max(self.ranges[last - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        IntervalSet::normalize(self.ranges.iter().chain(&other.ranges).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // step past whichever range finishes first
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            // skip the ranges that end before this one starts
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            // cut out every range that overlaps this one
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if start < other.ranges[k].start {
+                    ranges.push(start..other.ranges[k].start);
+                }
+                start = start.max(other.ranges[k].end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl<T: Ord + Copy> Default for IntervalSet<T> {
+    fn default() -> IntervalSet<T> {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Ord + Copy> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> IntervalSet<T> {
+        IntervalSet::normalize(vec![range])
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> IntervalSet<T> {
+        IntervalSet::normalize(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn members(set: &IntervalSet<u64>) -> BTreeSet<u64> {
+        set.ranges().iter().cloned().flatten().collect()
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<Range<u64>>> {
+        prop::collection::vec((0u64..60, 0u64..15).prop_map(|(s, l)| s..s + l), 0..6)
+    }
+
+    #[test]
+    fn check_normalize() {
+        let set: IntervalSet<u64> = [5..8, 1..3, 3..4, 7..10, 12..12, 20..25]
+            .into_iter()
+            .collect();
+        assert_eq!(set.ranges(), &[1..4, 5..10, 20..25]);
+        assert_eq!(set.min(), Some(1));
+        assert!(IntervalSet::<u64>::new().is_empty());
+    }
+
+    #[test]
+    fn check_operations() {
+        let a: IntervalSet<u64> = [0..10, 20..30].into_iter().collect();
+        let b = IntervalSet::from(5..25);
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..30]);
+        assert_eq!(b.difference(&a), IntervalSet::from(10..20));
+    }
+
+    proptest! {
+        #[test]
+        fn check_against_sets(a in ranges(), b in ranges(), extra in ranges()) {
+            let a: IntervalSet<u64> = a.into_iter().collect();
+            let b: IntervalSet<u64> = b.into_iter().collect();
+            let (x, y) = (members(&a), members(&b));
+
+            prop_assert_eq!(members(&a.union(&b)), &x | &y);
+            prop_assert_eq!(members(&a.intersection(&b)), &x & &y);
+            prop_assert_eq!(members(&a.difference(&b)), &x - &y);
+
+            // inserting one range at a time gives the same set as normalizing them all at once
+            let mut inserted = a.clone();
+            for range in extra.iter().cloned() {
+                inserted.insert(range);
+            }
+            let collected: IntervalSet<u64> = a.ranges().iter().cloned().chain(extra).collect();
+            prop_assert_eq!(inserted, collected);
+        }
+    }
+}
@@ -1,157 +1,313 @@
+use interval_set::IntervalSet;
+use range_map::{Overlap, RangeMap};
 use regex::Regex;
-use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt;
 use std::ops::Range;
 
+mod interval_set;
+mod range_map;
+
 fn main() {
     let input = include_str!("input.txt");
 
     println!("Part 1: {}", part1(input));
     println!("Part 2: {}", part2(input));
+    print_locate(input, &env::args().skip(1).collect::<Vec<_>>());
+}
+
+// `--locate 40..60,80..90` lists the seeds that end up at any of those locations, and which of the
+// almanac's seed ranges do and don't
+fn print_locate(input: &str, args: &[String]) {
+    let Some(position) = args.iter().position(|arg| arg == "--locate") else {
+        return;
+    };
+    let locations = args
+        .get(position + 1)
+        .unwrap_or_else(|| panic!("--locate needs a list of ranges"));
+
+    let almanac = parse_input(input);
+    let mut seeds = IntervalSet::new();
+    for range in locations.split(',') {
+        let (start, end) = range
+            .split_once("..")
+            .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+            .unwrap_or_else(|| panic!("{range} is not a range like 40..60"));
+        let found = almanac.preimage("seed", "location", start..end);
+        seeds = seeds.union(&found.unwrap_or_else(|e| panic!("{e}")));
+    }
+
+    let planted = &almanac.seed_ranges;
+    println!("Seeds: {}", show(&seeds));
+    println!(
+        "Planted seeds that reach them: {}",
+        show(&planted.intersection(&seeds))
+    );
+    println!(
+        "Planted seeds that don't: {}",
+        show(&planted.difference(&seeds))
+    );
+}
+
+fn show(set: &IntervalSet<u64>) -> String {
+    if set.is_empty() {
+        return "none".to_string();
+    }
+    let ranges: Vec<_> = set
+        .ranges()
+        .iter()
+        .map(|r| format!("{}..{}", r.start, r.end))
+        .collect();
+    ranges.join(", ")
+}
+
+#[derive(Debug, PartialEq)]
+enum MapperError {
+    Malformed(String),
+    Overlap(Range<u64>, Range<u64>),
+}
+
+impl From<Overlap> for MapperError {
+    fn from(Overlap(a, b): Overlap) -> MapperError {
+        MapperError::Overlap(a, b)
+    }
+}
+
+impl fmt::Display for MapperError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapperError::Malformed(line) => write!(f, "malformed range: {line}"),
+            MapperError::Overlap(a, b) => write!(f, "source ranges {a:?} and {b:?} overlap"),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-struct MapperRange {
-    source: Range<u64>,
-    destination: Range<u64>,
+#[derive(Debug, Default)]
+struct MapperBuilder {
+    // source ranges and where each one starts in the destination
+    ranges: Vec<(Range<u64>, u64)>,
 }
 
+impl MapperBuilder {
+    fn new() -> MapperBuilder {
+        MapperBuilder::default()
+    }
+
+    // parse a `destination source length` line
+    fn add_range(&mut self, range: &str) -> Result<&mut MapperBuilder, MapperError> {
+        let malformed = || MapperError::Malformed(range.to_string());
+        let r = range
+            .split_whitespace()
+            .map(|n| n.parse::<u64>().map_err(|_| malformed()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [destination, source, len] = r[..] else {
+            return Err(malformed());
+        };
+        let (Some(source_end), Some(_)) = (source.checked_add(len), destination.checked_add(len))
+        else {
+            return Err(malformed());
+        };
+
+        self.ranges.push((source..source_end, destination));
+        Ok(self)
+    }
+
+    // check the ranges don't overlap, the map fills in the gaps between them
+    fn build(self) -> Result<Mapper, MapperError> {
+        Ok(Mapper {
+            map: RangeMap::new(self.ranges)?,
+        })
+    }
+}
+
+/// A single almanac section.
 #[derive(Debug)]
 struct Mapper {
-    ranges: Vec<MapperRange>,
+    map: RangeMap,
 }
 
 impl Mapper {
-    fn new() -> Mapper {
-        Mapper { ranges: Vec::new() }
+    // parse a section of the almanac, the first line is the section's header
+    fn parse(chunk: &str) -> Result<Mapper, MapperError> {
+        let mut builder = MapperBuilder::new();
+        for line in chunk.lines().skip(1) {
+            builder.add_range(line)?;
+        }
+        builder.build()
     }
+}
 
-    fn add_range(&mut self, range: &str) {
-        let r: Vec<_> = range
-            .split_whitespace()
-            .map(|n| n.parse::<u64>().unwrap())
-            .collect();
-        let r = MapperRange {
-            source: r[1]..r[1] + r[2],
-            destination: r[0]..r[0] + r[2],
-        };
+// collapse a chain of mappers into a single map
+fn compose(mappers: &[&Mapper]) -> RangeMap {
+    mappers
+        .iter()
+        .fold(RangeMap::identity(), |acc, m| acc.compose(&m.map))
+}
+
+#[derive(Debug, PartialEq)]
+enum AlmanacError {
+    Malformed(String),
+    Mapper(MapperError),
+    Duplicate(String, String),
+    NoPath(String, String),
+}
 
-        match self
-            .ranges
-            .binary_search_by(|m| m.source.start.cmp(&r.source.start))
-        {
-            Err(pos) => self.ranges.insert(pos, r),
-            _ => {}
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlmanacError::Malformed(line) => write!(f, "malformed almanac section: {line}"),
+            AlmanacError::Mapper(e) => e.fmt(f),
+            AlmanacError::Duplicate(from, to) => write!(f, "{from}-to-{to} map is listed twice"),
+            AlmanacError::NoPath(from, to) => write!(f, "no way to map {from} to {to}"),
         }
     }
+}
+
+impl From<MapperError> for AlmanacError {
+    fn from(e: MapperError) -> AlmanacError {
+        AlmanacError::Mapper(e)
+    }
+}
+
+/// The seeds and the mappers between categories, keyed by the category they map from.
+#[derive(Debug)]
+struct Almanac<'a> {
+    seeds: Vec<u64>,
+    // the seeds read as pairs of a start and a length
+    seed_ranges: IntervalSet<u64>,
+    mappers: HashMap<&'a str, Vec<(&'a str, Mapper)>>,
+}
+
+impl<'a> Almanac<'a> {
+    fn parse(input: &'a str) -> Result<Almanac<'a>, AlmanacError> {
+        // sections are separated by empty lines
+        let re = Regex::new(r"(?m)\n\n").unwrap();
+        let mut iter = re.split(input);
+
+        // parse seeds from input
+        let line = iter.next().unwrap_or_default();
+        let malformed = || AlmanacError::Malformed(line.to_string());
+        let seeds: Vec<u64> = line
+            .strip_prefix("seeds:")
+            .ok_or_else(malformed)?
+            .split_whitespace()
+            .map(|s| s.parse::<u64>().map_err(|_| malformed()))
+            .collect::<Result<_, _>>()?;
+
+        // the same numbers as pairs, which have to be complete and fit in a u64
+        let seed_ranges = seeds
+            .chunks(2)
+            .map(|pair| match *pair {
+                [start, len] => start.checked_add(len).map(|end| start..end),
+                _ => None,
+            })
+            .collect::<Option<_>>()
+            .ok_or_else(malformed)?;
+
+        // parse mappers and the categories they map between from the section headers
+        let header = Regex::new(r"^(\w+)-to-(\w+) map:$").unwrap();
+        let mut mappers: HashMap<_, Vec<_>> = HashMap::new();
+        for chunk in iter {
+            let line = chunk.lines().next().unwrap_or_default();
+            let caps = header
+                .captures(line)
+                .ok_or_else(|| AlmanacError::Malformed(line.to_string()))?;
+            let (from, to) = (caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str());
 
-    // fill in gaps in the ranges created by the mapper
-    fn fill(&mut self) {
-        let mut current = 0;
-        for range in self.ranges.clone().into_iter().map(|m| m.source.clone()) {
-            if current < range.start {
-                self.add_range(format!("{current} {current} {}", range.start).as_str());
-                current = range.end
+            let edges = mappers.entry(from).or_default();
+            if edges.iter().any(|(dest, _)| *dest == to) {
+                return Err(AlmanacError::Duplicate(from.to_string(), to.to_string()));
             }
+            edges.push((to, Mapper::parse(chunk)?));
         }
+
+        Ok(Almanac {
+            seeds,
+            seed_ranges,
+            mappers,
+        })
     }
 
-    fn translate(&self, n: u64) -> u64 {
-        for range in &self.ranges {
-            if range.source.contains(&n) {
-                let diff = n - range.source.start;
-                // return the new number from the destination range
-                return range.destination.start + diff;
+    // breadth first search for the shortest chain of mappers between two categories
+    fn route(&self, from: &str, to: &str) -> Result<Vec<&Mapper>, AlmanacError> {
+        let mut previous: HashMap<&str, (&str, &Mapper)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                // follow the chain back to the start
+                let mut route = Vec::new();
+                let mut current = category;
+                while current != from {
+                    let (prev, mapper) = previous[current];
+                    route.push(mapper);
+                    current = prev;
+                }
+                route.reverse();
+                return Ok(route);
+            }
+            for (next, mapper) in self.mappers.get(category).into_iter().flatten() {
+                if *next != from && !previous.contains_key(next) {
+                    previous.insert(next, (category, mapper));
+                    queue.push_back(next);
+                }
             }
         }
-        // no range contains, return the original number
-        n
+        Err(AlmanacError::NoPath(from.to_string(), to.to_string()))
     }
-}
 
-fn parse_input(input: &str) -> (Vec<u64>, Vec<Mapper>) {
-    // sections are separated by empty lines
-    let re = Regex::new(r"(?m)\n\n").unwrap();
-    let mut iter = re.split(input);
-
-    // parse seeds from input
-    let seeds: Vec<u64> = iter
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect();
+    /// A single map from one category to another, composed along the shortest chain of sections.
+    fn path(&self, from: &str, to: &str) -> Result<RangeMap, AlmanacError> {
+        Ok(compose(&self.route(from, to)?))
+    }
 
-    // parse mappers and their ranges
-    let mappers: Vec<_> = iter
-        .map(|chunk| {
-            let mut mapper = Mapper::new();
-            chunk
-                .lines()
-                .skip(1)
-                .for_each(|line| mapper.add_range(line));
-            mapper.fill();
-            mapper
-        })
-        .collect();
+    /// Every number in the `from` category that ends up in `range` of the `to` category.
+    fn preimage(
+        &self,
+        from: &str,
+        to: &str,
+        range: Range<u64>,
+    ) -> Result<IntervalSet<u64>, AlmanacError> {
+        Ok(self.path(from, to)?.preimage(range).into_iter().collect())
+    }
+}
 
-    (seeds, mappers)
+fn parse_input(input: &str) -> Almanac<'_> {
+    Almanac::parse(input).unwrap_or_else(|e| panic!("{e}"))
 }
 
 fn part1(input: &str) -> u64 {
-    let (seeds, mappers) = parse_input(input);
+    let almanac = parse_input(input);
+    let locations = almanac.path("seed", "location").unwrap();
 
     // get location of each seed
-    let mut lowest: Option<u64> = None;
-    for seed in seeds {
-        let location = mappers.iter().fold(seed, |acc, m| m.translate(acc));
-        lowest = Some(cmp::min(location, lowest.unwrap_or(location)));
-    }
-    lowest.unwrap()
+    almanac
+        .seeds
+        .iter()
+        .map(|&seed| locations.get(seed))
+        .min()
+        .unwrap()
 }
 
 fn part2(input: &str) -> u64 {
-    let (seeds, mappers) = parse_input(input);
-
-    // get ranges of seeds
-    let mut seeds: Vec<_> = seeds.chunks(2).map(|r| r[0]..r[0] + r[1]).collect();
-
-    for mapper in mappers {
-        let mut translated = Vec::new();
-        while seeds.len() > 0 {
-            let seed = seeds.pop().unwrap();
-            let mut found = false;
-            for range in &mapper.ranges {
-                if range.source.contains(&seed.start) && range.source.contains(&seed.end) {
-                    // current seed's range is entirely within the current range
-                    translated.push(mapper.translate(seed.start)..mapper.translate(seed.end));
-                    found = true;
-                    break;
-                } else if range.source.contains(&seed.start) {
-                    // current seed's range starts in the current range, but overlaps into the next range
-                    translated
-                        .push(mapper.translate(seed.start)..mapper.translate(range.source.end - 1));
-                    // push the remaining part of this seed's range onto the seed vector
-                    seeds.push(range.source.end..seed.end);
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                // seed wasn't in any ranges, just push it onto the translated vector
-                translated.push(seed);
-            }
+    let almanac = parse_input(input);
+    let locations = almanac.path("seed", "location").unwrap();
+
+    // map every range of seeds all the way to the set of locations
+    let mut mapped = IntervalSet::new();
+    for seeds in almanac.seed_ranges.ranges() {
+        for range in locations.map_range(seeds.clone()) {
+            mapped.insert(range);
         }
-        seeds = translated;
     }
-
-    seeds.iter().map(|r| r.start).min().unwrap()
+    mapped.min().unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
+    use proptest::prelude::*;
 
     static INPUT: &str = indoc! {"
         seeds: 79 14 55 13
@@ -200,4 +356,293 @@ mod tests {
         let result = part2(INPUT);
         assert_eq!(result, 46)
     }
+
+    #[test]
+    fn check_preimage() {
+        let almanac = parse_input(INPUT);
+        let locations = almanac.path("seed", "location").unwrap();
+
+        // the lowest locations from part 1 and part 2
+        let contains = |set: &IntervalSet<u64>, n| set.ranges().iter().any(|r| r.contains(&n));
+        assert!(contains(
+            &almanac.preimage("seed", "location", 35..36).unwrap(),
+            13
+        ));
+        assert!(contains(
+            &almanac.preimage("seed", "location", 46..47).unwrap(),
+            82
+        ));
+
+        // every seed found maps back into the requested locations, and no other seed does
+        let seeds = almanac.preimage("seed", "location", 40..60).unwrap();
+        for seed in 0..200 {
+            assert_eq!(
+                contains(&seeds, seed),
+                (40..60).contains(&locations.get(seed)),
+                "seed {seed}"
+            );
+        }
+
+        // part way along the chain, and a chain that doesn't exist
+        let soil = almanac.path("seed", "soil").unwrap();
+        let seeds = almanac.preimage("seed", "soil", 50..53).unwrap();
+        assert!((0..200).all(|n| contains(&seeds, n) == (50..53).contains(&soil.get(n))));
+        assert_eq!(
+            almanac.preimage("location", "seed", 0..1).unwrap_err(),
+            AlmanacError::NoPath("location".to_string(), "seed".to_string())
+        );
+        assert_eq!(almanac.seed_ranges.ranges(), &[55..68, 79..93]);
+    }
+
+    #[test]
+    fn check_mapper_fill() {
+        let mapper = Mapper::parse(indoc! {"
+            seed-to-soil map:
+            50 98 2
+            0 10 5
+            52 50 48
+            1000 200 3
+            7 300 0"
+        })
+        .unwrap();
+        // the map fills the gaps, an empty range maps nothing
+        let expected = RangeMap::new([(98..100, 50), (10..15, 0), (50..98, 52), (200..203, 1000)]);
+        assert_eq!(mapper.map, expected.unwrap());
+        assert_eq!(mapper.map.get(5), 5);
+        assert_eq!(mapper.map.get(300), 300);
+        assert_eq!(mapper.map.get(u64::MAX - 1), u64::MAX - 1);
+    }
+
+    #[test]
+    fn check_mapper_overlap() {
+        let mut builder = MapperBuilder::new();
+        builder
+            .add_range("50 98 2")
+            .unwrap()
+            .add_range("0 99 5")
+            .unwrap();
+        assert_eq!(
+            builder.build().unwrap_err(),
+            MapperError::Overlap(98..100, 99..104)
+        );
+
+        // ranges with the same start used to be silently dropped
+        let mut builder = MapperBuilder::new();
+        builder
+            .add_range("50 98 2")
+            .unwrap()
+            .add_range("0 98 1")
+            .unwrap();
+        assert_eq!(
+            builder.build().unwrap_err(),
+            MapperError::Overlap(98..100, 98..99)
+        );
+
+        let mut builder = MapperBuilder::new();
+        assert_eq!(
+            builder.add_range("1 2").unwrap_err(),
+            MapperError::Malformed("1 2".to_string())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn check_mapper_bijection(
+            base in 0u64..1000,
+            (sizes, order) in prop::collection::vec(1u64..20, 1..10)
+                .prop_flat_map(|sizes| {
+                    let order: Vec<_> = (0..sizes.len()).collect();
+                    (Just(sizes), Just(order).prop_shuffle())
+                }),
+        ) {
+            // cut `base..base + total` into blocks and send each block to a shuffled position
+            let starts: Vec<_> = sizes
+                .iter()
+                .scan(base, |acc, size| {
+                    let start = *acc;
+                    *acc += size;
+                    Some(start)
+                })
+                .collect();
+            let total: u64 = sizes.iter().sum();
+            let mut destination = base;
+            let mut builder = MapperBuilder::new();
+            for &i in &order {
+                builder.add_range(&format!("{destination} {} {}", starts[i], sizes[i])).unwrap();
+                destination += sizes[i];
+            }
+            let mapper = builder.build().unwrap();
+
+            // every number in the domain has exactly one image, and every image exactly one source
+            let map = &mapper.map;
+            let mut images: Vec<_> = (base..base + total).map(|n| map.get(n)).collect();
+            images.sort();
+            prop_assert_eq!(images, (base..base + total).collect::<Vec<_>>());
+            for n in base.saturating_sub(5)..base + total + 5 {
+                let image = map.get(n);
+                prop_assert_eq!(map.preimage(image..image + 1), vec![n..n + 1]);
+            }
+        }
+
+        #[test]
+        fn check_mapper_overlaps(ranges in prop::collection::vec((0u64..100, 1u64..20), 1..6)) {
+            let overlapping = ranges.iter().enumerate().any(|(i, &(a, a_len))| {
+                ranges[i + 1..]
+                    .iter()
+                    .any(|&(b, b_len)| a < b + b_len && b < a + a_len)
+            });
+
+            let mut builder = MapperBuilder::new();
+            for (source, len) in &ranges {
+                builder.add_range(&format!("{} {source} {len}", source + 1000)).unwrap();
+            }
+            match builder.build() {
+                Ok(mapper) => {
+                    prop_assert!(!overlapping);
+                    // numbers in a range move up by 1000, and the gaps between them stay put
+                    for n in 0..130 {
+                        let moved = ranges.iter().any(|&(source, len)| (source..source + len).contains(&n));
+                        prop_assert_eq!(mapper.map.get(n), if moved { n + 1000 } else { n });
+                    }
+                }
+                Err(e) => {
+                    prop_assert!(overlapping);
+                    prop_assert!(matches!(e, MapperError::Overlap(..)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_almanac_paths() {
+        let almanac = parse_input(INPUT);
+        let soil = almanac.path("seed", "soil").unwrap();
+        let humidity = almanac.path("soil", "humidity").unwrap();
+        let location = almanac.path("seed", "location").unwrap();
+        let last = almanac.path("humidity", "location").unwrap();
+        for seed in 0..200 {
+            assert_eq!(last.get(humidity.get(soil.get(seed))), location.get(seed));
+        }
+        assert_eq!(almanac.path("soil", "soil").unwrap(), RangeMap::identity());
+        assert_eq!(
+            almanac.path("location", "seed").unwrap_err(),
+            AlmanacError::NoPath("location".to_string(), "seed".to_string())
+        );
+    }
+
+    #[test]
+    fn check_almanac_reordered() {
+        // reverse the order of the sections, but keep the seeds first
+        let mut sections: Vec<_> = INPUT.split("\n\n").collect();
+        sections[1..].reverse();
+        let input = sections.join("\n\n");
+        assert_eq!(part1(&input), 35);
+        assert_eq!(part2(&input), 46);
+    }
+
+    #[test]
+    fn check_almanac_branches() {
+        let almanac = parse_input(indoc! {"
+            seeds: 1 2
+
+            seed-to-soil map:
+            10 0 5
+
+            seed-to-water map:
+            20 0 5
+
+            soil-to-fertilizer map:
+            100 10 5
+
+            fertilizer-to-location map:
+            0 100 1
+
+            water-to-light map:
+            30 20 5"
+        });
+        assert_eq!(almanac.path("seed", "light").unwrap().get(1), 31);
+        assert_eq!(almanac.path("seed", "location").unwrap().get(0), 0);
+        assert_eq!(almanac.path("seed", "location").unwrap().get(1), 101);
+        assert!(almanac.path("water", "location").is_err());
+
+        let duplicate = indoc! {"
+            seeds: 1 2
+
+            seed-to-soil map:
+            10 0 5
+
+            seed-to-soil map:
+            20 0 5"
+        };
+        assert_eq!(
+            Almanac::parse(duplicate).unwrap_err(),
+            AlmanacError::Duplicate("seed".to_string(), "soil".to_string())
+        );
+
+        // the seeds have to pair up into ranges that fit in a u64
+        for seeds in ["seeds: 1 2 3", &format!("seeds: {} 2", u64::MAX - 1)] {
+            assert_eq!(
+                Almanac::parse(seeds).unwrap_err(),
+                AlmanacError::Malformed(seeds.to_string())
+            );
+        }
+    }
+
+    // a (destination, source, length) triple for each line of a section
+    type Section = Vec<(u64, u64, u64)>;
+
+    // small sections whose source ranges don't overlap
+    fn section() -> impl Strategy<Value = Section> {
+        prop::collection::vec((0u64..60, 0u64..6, 1u64..8), 0..5).prop_map(|ranges| {
+            // lay the sources out one after another so they never overlap
+            let mut source = 0;
+            ranges
+                .into_iter()
+                .map(|(destination, gap, len)| {
+                    source += gap;
+                    let range = (destination, source, len);
+                    source += len;
+                    range
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn check_part2_brute_force(
+            seeds in prop::collection::vec((0u64..60, 1u64..10), 1..4),
+            sections in prop::collection::vec(section(), 1..4),
+        ) {
+            let names = ["seed", "soil", "water", "light", "location"];
+            let mut input = String::from("seeds:");
+            for (start, len) in &seeds {
+                input.push_str(&format!(" {start} {len}"));
+            }
+            let n = sections.len();
+            for (i, section) in sections.iter().enumerate() {
+                let to = if i + 1 == n { "location" } else { names[i + 1] };
+                input.push_str(&format!("\n\n{}-to-{to} map:", names[i]));
+                for (destination, source, len) in section {
+                    input.push_str(&format!("\n{destination} {source} {len}"));
+                }
+            }
+
+            // translate every seed one at a time straight from the section lines
+            let lowest = seeds
+                .iter()
+                .flat_map(|&(start, len)| start..start + len)
+                .map(|seed| {
+                    sections.iter().fold(seed, |n, section| {
+                        section
+                            .iter()
+                            .find(|&&(_, source, len)| (source..source + len).contains(&n))
+                            .map_or(n, |&(destination, source, _)| destination + (n - source))
+                    })
+                })
+                .min()
+                .unwrap();
+            prop_assert_eq!(part2(&input), lowest);
+        }
+    }
 }
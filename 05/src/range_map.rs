@@ -0,0 +1,278 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    source: Range<u64>,
+    destination: u64,
+}
+
+impl Segment {
+    fn translate(&self, n: u64) -> u64 {
+        self.destination + (n - self.source.start)
+    }
+
+    // two segments can be merged if the second one continues the first one's translation
+    fn continues(&self, next: &Segment) -> bool {
+        self.source.end == next.source.start && self.translate_end() == next.destination
+    }
+
+    fn translate_end(&self) -> u64 {
+        self.destination + (self.source.end - self.source.start)
+    }
+}
+
+/// Two source ranges passed to `RangeMap::new` that overlap, in sorted order.
+#[derive(Debug, PartialEq)]
+pub struct Overlap(pub Range<u64>, pub Range<u64>);
+
+/// A piecewise-linear map over `u64`, every number that isn't covered by a
+/// segment maps to itself.
+///
+/// Internally the segments are kept sorted and cover the whole domain
+/// `0..u64::MAX` so lookups are a binary search and composition is a merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeMap {
+    segments: Vec<Segment>,
+}
+
+impl RangeMap {
+    /// Build a map from `(source, destination start)` pairs, failing if two source ranges overlap.
+    pub fn new<I>(ranges: I) -> Result<RangeMap, Overlap>
+    where
+        I: IntoIterator<Item = (Range<u64>, u64)>,
+    {
+        let mut ranges: Vec<_> = ranges
+            .into_iter()
+            .filter(|(source, _)| !source.is_empty())
+            .map(|(source, destination)| Segment {
+                source,
+                destination,
+            })
+            .collect();
+        ranges.sort_by_key(|s| s.source.start);
+
+        // fill in the gaps with identity segments, the last segment pushed is never one of those
+        let mut segments: Vec<Segment> = Vec::with_capacity(ranges.len() * 2 + 1);
+        let mut current = 0;
+        for segment in ranges {
+            if let Some(last) = segments.last() {
+                if last.source.end > segment.source.start {
+                    return Err(Overlap(last.source.clone(), segment.source));
+                }
+            }
+            if current < segment.source.start {
+                segments.push(Segment {
+                    source: current..segment.source.start,
+                    destination: current,
+                });
+            }
+            current = segment.source.end;
+            segments.push(segment);
+        }
+        if current < u64::MAX {
+            segments.push(Segment {
+                source: current..u64::MAX,
+                destination: current,
+            });
+        }
+
+        Ok(RangeMap::from_segments(segments))
+    }
+
+    /// The map that sends every number to itself.
+    pub fn identity() -> RangeMap {
+        RangeMap {
+            segments: vec![Segment {
+                source: 0..u64::MAX,
+                destination: 0,
+            }],
+        }
+    }
+
+    // merge neighbouring segments that translate by the same amount
+    fn from_segments(segments: Vec<Segment>) -> RangeMap {
+        let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+        for segment in segments {
+            match merged.last_mut() {
+                Some(last) if last.continues(&segment) => last.source.end = segment.source.end,
+                _ => merged.push(segment),
+            }
+        }
+        RangeMap { segments: merged }
+    }
+
+    // index of the segment containing `n`, if any
+    fn find(&self, n: u64) -> Option<usize> {
+        let i = self.segments.partition_point(|s| s.source.end <= n);
+        (i < self.segments.len()).then_some(i)
+    }
+
+    /// Translate a single number.
+    pub fn get(&self, n: u64) -> u64 {
+        match self.find(n) {
+            Some(i) => self.segments[i].translate(n),
+            None => n,
+        }
+    }
+
+    /// Translate a range of numbers, the result is one range per segment the input overlaps.
+    pub fn map_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+        if range.is_empty() {
+            return mapped;
+        }
+
+        let mut current = range.start;
+        if let Some(i) = self.find(current) {
+            for segment in &self.segments[i..] {
+                if current >= range.end {
+                    break;
+                }
+                let end = range.end.min(segment.source.end);
+                mapped.push(segment.translate(current)..segment.translate(end - 1) + 1);
+                current = end;
+            }
+        }
+        // only `u64::MAX` itself can be left over, it's outside every segment
+        if current < range.end {
+            mapped.push(current..range.end);
+        }
+        mapped
+    }
+
+    /// Every number that translates into `range`, as sorted and non-overlapping ranges.
+    pub fn preimage(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let mut preimage: Vec<Range<u64>> = Vec::new();
+        for segment in &self.segments {
+            // intersect this segment's image with the range and translate it back
+            let start = range.start.max(segment.destination);
+            let end = range.end.min(segment.translate_end());
+            if start >= end {
+                continue;
+            }
+            let source = segment.source.start + (start - segment.destination);
+            let source = source..source + (end - start);
+            match preimage.last_mut() {
+                Some(last) if last.end == source.start => last.end = source.end,
+                _ => preimage.push(source),
+            }
+        }
+        preimage
+    }
+
+    /// The map equivalent to applying `self` and then `next`.
+    pub fn compose(&self, next: &RangeMap) -> RangeMap {
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            // split this segment wherever its image crosses a segment boundary in `next`
+            let mut source = segment.source.start;
+            for image in next.map_range(segment.destination..segment.translate_end()) {
+                let len = image.end - image.start;
+                segments.push(Segment {
+                    source: source..source + len,
+                    destination: image.start,
+                });
+                source += len;
+            }
+        }
+        RangeMap::from_segments(segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the sources must tile `0..u64::MAX` without gaps
+    fn assert_contiguous(map: &RangeMap) {
+        let mut current = 0;
+        for segment in &map.segments {
+            assert_eq!(segment.source.start, current);
+            current = segment.source.end;
+        }
+        assert_eq!(current, u64::MAX);
+    }
+
+    #[test]
+    fn check_fill() {
+        let map =
+            RangeMap::new([(98..100, 50), (10..15, 0), (50..98, 52), (200..203, 1000)]).unwrap();
+        assert_contiguous(&map);
+        let sources: Vec<_> = map.segments.iter().map(|s| s.source.clone()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                0..10,
+                10..15,
+                15..50,
+                50..98,
+                98..100,
+                100..200,
+                200..203,
+                203..u64::MAX
+            ]
+        );
+        assert_contiguous(&RangeMap::new([]).unwrap());
+        assert_eq!(RangeMap::new([]).unwrap(), RangeMap::identity());
+    }
+
+    #[test]
+    fn check_overlap() {
+        assert_eq!(
+            RangeMap::new([(98..100, 50), (99..104, 0)]),
+            Err(Overlap(98..100, 99..104))
+        );
+        assert_eq!(
+            RangeMap::new([(98..100, 50), (98..99, 0)]),
+            Err(Overlap(98..100, 98..99))
+        );
+        // touching ranges are fine, and so are empty ones
+        assert!(RangeMap::new([(0..5, 10), (5..10, 0), (7..7, 3)]).is_ok());
+    }
+
+    #[test]
+    fn check_get() {
+        let map = RangeMap::new([(98..100, 50), (50..98, 52)]).unwrap();
+        assert_eq!(map.get(0), 0);
+        assert_eq!(map.get(49), 49);
+        assert_eq!(map.get(50), 52);
+        assert_eq!(map.get(97), 99);
+        assert_eq!(map.get(98), 50);
+        assert_eq!(map.get(99), 51);
+        assert_eq!(map.get(100), 100);
+        assert_eq!(map.get(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn check_map_range() {
+        let map = RangeMap::new([(98..100, 50), (50..98, 52)]).unwrap();
+        assert_eq!(map.map_range(45..55), vec![45..50, 52..57]);
+        assert_eq!(map.map_range(96..102), vec![98..100, 50..52, 100..102]);
+        assert_eq!(map.map_range(10..10), vec![]);
+    }
+
+    #[test]
+    fn check_compose() {
+        let first = RangeMap::new([(98..100, 50), (50..98, 52)]).unwrap();
+        let second = RangeMap::new([(15..52, 0), (52..54, 37), (0..15, 39)]).unwrap();
+        let composed = first.compose(&second);
+        for n in 0..200 {
+            assert_eq!(composed.get(n), second.get(first.get(n)), "seed {n}");
+        }
+        assert_eq!(RangeMap::identity().compose(&first), first);
+        assert_eq!(first.compose(&RangeMap::identity()), first);
+    }
+
+    #[test]
+    fn check_preimage() {
+        let map = RangeMap::new([(98..100, 50), (50..98, 52)]).unwrap();
+        assert_eq!(map.preimage(50..52), vec![98..100]);
+        assert_eq!(map.preimage(48..53), vec![48..51, 98..100]);
+        assert_eq!(map.preimage(99..101), vec![97..98, 100..101]);
+
+        // a map that isn't injective has a preimage in several places
+        let map = RangeMap::new([(0..10, 100)]).unwrap();
+        assert_eq!(map.preimage(100..102), vec![0..2, 100..102]);
+        assert_eq!(map.preimage(5..8), vec![]);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::Race;
+use std::ops::{Range, RangeInclusive};
+
+/// How far a boat travels in a race.
+///
+/// The distance has to rise and then fall as the hold time grows (it may stay
+/// level at the top), which is what lets the solver binary search each side of
+/// the peak when a model doesn't have a closed form.
+pub trait BoatModel {
+    /// The distance travelled when the button is held for `hold` of the race's `time`.
+    fn distance(&self, hold: u64, time: u64) -> u128;
+
+    /// Every hold time that beats the record.
+    fn winning_holds(&self, race: &Race<u64>) -> Option<RangeInclusive<u64>> {
+        search(self, race)
+    }
+
+    fn ways_to_win(&self, race: &Race<u64>) -> u64 {
+        self.winning_holds(race)
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+}
+
+/// The puzzle's boat: speed is the hold time and it keeps that speed for the rest of the race.
+pub struct Linear;
+
+impl BoatModel for Linear {
+    fn distance(&self, hold: u64, time: u64) -> u128 {
+        hold as u128 * (time - hold) as u128
+    }
+
+    fn winning_holds(&self, race: &Race<u64>) -> Option<RangeInclusive<u64>> {
+        race.winning_holds()
+    }
+}
+
+/// Holding gains `acceleration` speed every millisecond until the boat reaches its `cap`.
+pub struct CappedAcceleration {
+    pub acceleration: u64,
+    pub cap: u64,
+}
+
+impl BoatModel for CappedAcceleration {
+    fn distance(&self, hold: u64, time: u64) -> u128 {
+        let speed = (hold as u128 * self.acceleration as u128).min(self.cap as u128);
+        speed * (time - hold) as u128
+    }
+
+    fn winning_holds(&self, race: &Race<u64>) -> Option<RangeInclusive<u64>> {
+        let (time, record) = (race.time, race.distance);
+        if self.acceleration == 0 || self.cap == 0 {
+            return None;
+        }
+
+        // up to `capped` the distance is `acceleration` times the linear boat's, and an integer
+        // times `acceleration` beats the record exactly when it beats `record / acceleration`
+        let capped = self.cap / self.acceleration;
+        let speeding_up = Race {
+            time,
+            distance: record / self.acceleration,
+        }
+        .winning_holds()
+        .map(|holds| *holds.start()..=capped.min(*holds.end()))
+        .filter(|holds| !holds.is_empty());
+
+        // after that the speed is `cap` and it wins while more than `record / cap` is left
+        let last = time
+            .checked_sub(record / self.cap)
+            .and_then(|left| left.checked_sub(1));
+        let at_cap = last
+            .map(|last| capped.saturating_add(1)..=last)
+            .filter(|holds| !holds.is_empty());
+
+        // the winning holds are one run, so if both sides win they meet at `capped`
+        match (speeding_up, at_cap) {
+            (Some(first), Some(second)) => Some(*first.start()..=*second.end()),
+            (first, second) => first.or(second),
+        }
+    }
+}
+
+/// Once released the boat loses `decay` speed every millisecond until it stops.
+pub struct Friction {
+    pub decay: u64,
+}
+
+impl BoatModel for Friction {
+    fn distance(&self, hold: u64, time: u64) -> u128 {
+        let (speed, decay) = (hold as u128, self.decay as u128);
+        if decay == 0 {
+            return speed * (time - hold) as u128;
+        }
+
+        // the boat moves for as many milliseconds as it takes to slow to a stop,
+        // the distance is the sum of speed - decay * k over those milliseconds
+        let moving = (time - hold) as u128;
+        let moving = moving.min(speed.div_ceil(decay));
+        moving * speed - decay * moving * moving.saturating_sub(1) / 2
+    }
+}
+
+/// A model from the command line: `linear`, `capped:ACCELERATION,CAP` or `friction:DECAY`.
+pub fn parse_model(spec: &str) -> Option<Box<dyn BoatModel>> {
+    let (name, parameters) = spec.split_once(':').unwrap_or((spec, ""));
+    let parameters = parameters
+        .split(',')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    match (name, &parameters[..]) {
+        ("linear", []) => Some(Box::new(Linear)),
+        ("capped", &[acceleration, cap]) => {
+            Some(Box::new(CappedAcceleration { acceleration, cap }))
+        }
+        ("friction", &[decay]) => Some(Box::new(Friction { decay })),
+        _ => None,
+    }
+}
+
+// first value in `range` where `pred` is false, or the end of the range if there isn't one,
+// `pred` must be true and then false
+fn partition_point(range: Range<u64>, pred: impl Fn(u64) -> bool) -> u64 {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+// find the peak, then binary search for the record on the rising and falling sides of it
+fn search<M: BoatModel + ?Sized>(model: &M, race: &Race<u64>) -> Option<RangeInclusive<u64>> {
+    let time = race.time;
+    let record = race.distance as u128;
+    let distance = |hold| model.distance(hold, time);
+
+    let peak = partition_point(0..time, |h| distance(h) < distance(h + 1));
+    if distance(peak) <= record {
+        return None;
+    }
+
+    // the peak wins, so the search on each side stops before going past it, and `time` is
+    // checked on its own so the end of the range never has to go past `u64::MAX`
+    let low = partition_point(0..peak, |h| distance(h) <= record);
+    let high = match partition_point(peak..time, |h| distance(h) > record) {
+        end if end == time && distance(time) > record => time,
+        end => end - 1,
+    };
+    Some(low..=high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(model: &impl BoatModel, race: &Race<u64>) -> Option<RangeInclusive<u64>> {
+        let holds: Vec<_> = (0..=race.time)
+            .filter(|&h| model.distance(h, race.time) > race.distance as u128)
+            .collect();
+        holds.first().map(|&low| low..=*holds.last().unwrap())
+    }
+
+    fn check_model(model: &impl BoatModel) {
+        for time in 0..50 {
+            let best = (0..=time).map(|h| model.distance(h, time)).max().unwrap() as u64;
+            for distance in 0..best + 2 {
+                let race = Race { time, distance };
+                assert_eq!(
+                    model.winning_holds(&race),
+                    brute_force(model, &race),
+                    "{race:?}"
+                );
+                assert_eq!(search(model, &race), brute_force(model, &race), "{race:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn check_linear() {
+        check_model(&Linear);
+    }
+
+    #[test]
+    fn check_capped_acceleration() {
+        for acceleration in 1..4 {
+            for cap in [0, 1, 5, 12, 100] {
+                check_model(&CappedAcceleration { acceleration, cap });
+            }
+        }
+    }
+
+    #[test]
+    fn check_long_race() {
+        // the searches never need a bound past the end of the race
+        let race = Race {
+            time: u64::MAX,
+            distance: u64::MAX,
+        };
+        assert_eq!(search(&Linear, &race), Some(2..=u64::MAX - 2));
+        let capped = CappedAcceleration {
+            acceleration: 1,
+            cap: u64::MAX,
+        };
+        assert_eq!(capped.winning_holds(&race), Some(2..=u64::MAX - 2));
+        assert_eq!(search(&capped, &race), Some(2..=u64::MAX - 2));
+
+        // a boat capped at speed 1 wins by holding as little as it can
+        let capped = CappedAcceleration {
+            acceleration: 5,
+            cap: 1,
+        };
+        let race = Race {
+            time: u64::MAX,
+            distance: u64::MAX - 3,
+        };
+        assert_eq!(capped.winning_holds(&race), Some(1..=2));
+        assert_eq!(search(&capped, &race), Some(1..=2));
+    }
+
+    #[test]
+    fn check_parse_model() {
+        let race = Race {
+            time: 30,
+            distance: 200,
+        };
+        let ways = |spec| parse_model(spec).map(|model| model.ways_to_win(&race));
+        assert_eq!(ways("linear"), Some(Linear.ways_to_win(&race)));
+        let capped = CappedAcceleration {
+            acceleration: 2,
+            cap: 12,
+        };
+        assert_eq!(ways("capped:2,12"), Some(capped.ways_to_win(&race)));
+        assert_eq!(
+            ways("friction:1"),
+            Some(Friction { decay: 1 }.ways_to_win(&race))
+        );
+        for spec in ["", "linear:1", "capped:2", "friction:x", "warp:9"] {
+            assert!(parse_model(spec).is_none(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn check_friction() {
+        assert_eq!(Friction { decay: 2 }.distance(5, 10), 5 + 3 + 1);
+        assert_eq!(Friction { decay: 1 }.distance(3, 5), 3 + 2);
+        for decay in 0..4 {
+            check_model(&Friction { decay });
+        }
+    }
+}
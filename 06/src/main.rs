@@ -1,71 +1,172 @@
+use boat::{BoatModel, Linear};
+use num_bigint::BigUint;
+use num_integer::{Integer, Roots};
+use num_traits::{One, Zero};
+use std::env;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+mod boat;
+
 fn main() {
     let input = include_str!("input.txt");
+    let args: Vec<_> = env::args().skip(1).collect();
 
-    println!("Part 1: {}", part1(input));
+    println!("Part 1: {}", part1(input, boat_model(&args).as_ref()));
     println!("Part 2: {}", part2(input));
 }
 
-fn parse_input(input: &str) -> Vec<(u32, u32)> {
-    let time: Vec<_> = input
-        .lines()
-        .nth(0) // first line contains Time
-        .unwrap()
-        .split_whitespace()
-        .skip(1) // skip the line label
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
-    let distance: Vec<_> = input
-        .lines()
-        .nth(1) // second line contains Distance
-        .unwrap()
-        .split_whitespace()
-        .skip(1) // skip the line label
-        .map(|n| n.parse::<u32>().unwrap())
-        .collect();
+// `--model capped:2,10` or `--model friction:3` races a different boat in part 1
+fn boat_model(args: &[String]) -> Box<dyn BoatModel> {
+    let Some(position) = args.iter().position(|arg| arg == "--model") else {
+        return Box::new(Linear);
+    };
+    let spec = args.get(position + 1).map_or("", String::as_str);
+    boat::parse_model(spec).unwrap_or_else(|| {
+        panic!(
+            "unknown boat model {spec}, expected linear, capped:ACCELERATION,CAP or friction:DECAY"
+        )
+    })
+}
+
+/// An integer type a race can be measured in.
+///
+/// The solver squares the race time, so the arithmetic is done in a wider type
+/// that can't overflow and the results are narrowed back down afterwards.
+trait RaceInt: Clone + Ord + FromStr + fmt::Debug + fmt::Display {
+    type Wide: Integer + Roots + Clone + From<Self>;
+
+    fn narrow(wide: Self::Wide) -> Self;
+}
 
-    // return tuples of (time, distance)
-    time.into_iter().zip(distance.into_iter()).collect()
+impl RaceInt for u64 {
+    type Wide = u128;
+
+    fn narrow(wide: u128) -> u64 {
+        u64::try_from(wide).unwrap()
+    }
 }
 
-fn part1(input: &str) -> u32 {
-    let races = parse_input(input);
-    let mut wins = Vec::new();
-    for (i, (time, distance)) in races.into_iter().enumerate() {
-        wins.push(0);
-        for (hold, accelerate) in (0..=time).rev().enumerate() {
-            if hold as u32 * accelerate > distance {
-                wins[i] += 1;
+impl RaceInt for u128 {
+    type Wide = BigUint;
+
+    fn narrow(wide: BigUint) -> u128 {
+        u128::try_from(wide).unwrap()
+    }
+}
+
+impl RaceInt for BigUint {
+    type Wide = BigUint;
+
+    fn narrow(wide: BigUint) -> BigUint {
+        wide
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Race<T> {
+    time: T,
+    distance: T,
+}
+
+impl<T: RaceInt> Race<T> {
+    fn wins(&self, hold: &T::Wide) -> bool {
+        let time = T::Wide::from(self.time.clone());
+        hold.clone() * (time - hold.clone()) > T::Wide::from(self.distance.clone())
+    }
+
+    // the hold times that beat the record are the integers strictly between the roots of
+    // h * (t - h) = d, which are (t ± sqrt(t² - 4d)) / 2
+    fn winning_holds(&self) -> Option<RangeInclusive<T>> {
+        let time = T::Wide::from(self.time.clone());
+        let distance = T::Wide::from(self.distance.clone());
+        let one = T::Wide::one();
+        let two = one.clone() + one.clone();
+        let four = two.clone() * two.clone();
+
+        let square = time.clone() * time.clone();
+        let record = four * distance;
+        if square < record {
+            return None;
+        }
+
+        // the integer square root can put the estimate one either side of the real root
+        let half = time.clone() / two.clone();
+        let mut low = (time.clone() - (square - record).sqrt()) / two;
+        while !low.is_zero() && self.wins(&(low.clone() - one.clone())) {
+            low = low - one.clone();
+        }
+        while low <= half && !self.wins(&low) {
+            low = low + one.clone();
+        }
+        if low > half {
+            return None;
+        }
+
+        // the distance is symmetric around half the race time
+        let high = time - low.clone();
+        Some(T::narrow(low)..=T::narrow(high))
+    }
+
+    fn ways_to_win(&self) -> T {
+        match self.winning_holds() {
+            Some(holds) => {
+                let (low, high) = holds.into_inner();
+                T::narrow(T::Wide::from(high) - T::Wide::from(low) + T::Wide::one())
             }
+            None => T::narrow(T::Wide::zero()),
         }
     }
-    wins.into_iter().reduce(|acc, e| acc * e).unwrap()
 }
 
-fn part2(input: &str) -> u32 {
-    let time: Vec<_> = input
+// the numbers on a line after its label
+fn parse_line<'a>(input: &'a str, label: &str) -> Vec<&'a str> {
+    input
         .lines()
-        .nth(0) // first line contains Time
+        .find_map(|line| line.strip_prefix(label))
         .unwrap()
         .split_whitespace()
-        .skip(1) // skip the line label
-        .collect();
-    let time = time.join("").parse::<u64>().unwrap();
-    let distance: Vec<_> = input
-        .lines()
-        .nth(1) // second line contains Distance
-        .unwrap()
-        .split_whitespace()
-        .skip(1) // skip the line label
-        .collect();
-    let distance = distance.join("").parse::<u64>().unwrap();
-
-    let mut wins = 0;
-    for (hold, accelerate) in (0..=time).rev().enumerate() {
-        if hold as u64 * accelerate > distance {
-            wins += 1;
-        }
+        .collect()
+}
+
+fn parse_input<T: RaceInt>(input: &str) -> Vec<Race<T>>
+where
+    T::Err: fmt::Debug,
+{
+    let time = parse_line(input, "Time:");
+    let distance = parse_line(input, "Distance:");
+
+    time.into_iter()
+        .zip(distance)
+        .map(|(time, distance)| Race {
+            time: time.parse().unwrap(),
+            distance: distance.parse().unwrap(),
+        })
+        .collect()
+}
+
+// the kerning was wrong, there's only one race with all the digits on each line
+fn parse_kerned<T: RaceInt>(input: &str) -> Race<T>
+where
+    T::Err: fmt::Debug,
+{
+    Race {
+        time: parse_line(input, "Time:").join("").parse().unwrap(),
+        distance: parse_line(input, "Distance:").join("").parse().unwrap(),
     }
-    wins
+}
+
+fn part1(input: &str, model: &dyn BoatModel) -> u64 {
+    parse_input::<u64>(input)
+        .iter()
+        .map(|r| model.ways_to_win(r))
+        .product()
+}
+
+// the digits of every race joined together can be any length, so use a big integer
+fn part2(input: &str) -> BigUint {
+    parse_kerned::<BigUint>(input).ways_to_win()
 }
 
 #[cfg(test)]
@@ -80,13 +181,110 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let result = part1(INPUT);
+        let result = part1(INPUT, &Linear);
         assert_eq!(result, 288)
     }
 
+    #[test]
+    fn check_model_option() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(part1(INPUT, boat_model(&args(&[])).as_ref()), 288);
+        let model = boat_model(&args(&["--model", "capped:1,1000"]));
+        assert_eq!(part1(INPUT, model.as_ref()), 288);
+        // topping out at 10 wins the first race with longer holds and the others with fewer
+        let model = boat_model(&args(&["--model", "capped:2,10"]));
+        assert_eq!(part1(INPUT, model.as_ref()), 6 * 9 * 6);
+    }
+
     #[test]
     fn check_part2() {
         let result = part2(INPUT);
-        assert_eq!(result, 71503)
+        assert_eq!(result, BigUint::from(71503_u32))
+    }
+
+    #[test]
+    fn check_winning_holds() {
+        let races = parse_input::<u64>(INPUT);
+        assert_eq!(races[0].winning_holds(), Some(2..=5));
+        assert_eq!(races[1].winning_holds(), Some(4..=11));
+        assert_eq!(races[2].winning_holds(), Some(11..=19));
+        assert_eq!(parse_kerned::<u64>(INPUT).winning_holds(), Some(14..=71516));
+
+        // a perfect square discriminant only ties the record at the roots
+        let race = Race::<u64> {
+            time: 10,
+            distance: 24,
+        };
+        assert_eq!(race.winning_holds(), Some(5..=5));
+        let race = Race::<u64> {
+            time: 10,
+            distance: 25,
+        };
+        assert_eq!(race.winning_holds(), None);
+        let race = Race {
+            time: u64::MAX,
+            distance: u64::MAX,
+        };
+        assert_eq!(race.winning_holds(), Some(2..=u64::MAX - 2));
+        let race = Race {
+            time: u128::MAX,
+            distance: u128::MAX,
+        };
+        assert_eq!(race.winning_holds(), Some(2..=u128::MAX - 2));
+    }
+
+    #[test]
+    fn check_against_brute_force() {
+        for time in 0..60_u64 {
+            for distance in 0..time * time / 4 + 2 {
+                let race = Race { time, distance };
+                let holds: Vec<_> = (0..=time).filter(|&h| race.wins(&(h as u128))).collect();
+                let expected = holds.first().map(|&low| low..=*holds.last().unwrap());
+                assert_eq!(race.winning_holds(), expected, "{race:?}");
+
+                // every backend agrees
+                let wide = Race::<u128> {
+                    time: time as u128,
+                    distance: distance as u128,
+                };
+                assert_eq!(wide.ways_to_win(), race.ways_to_win() as u128);
+                let big = Race {
+                    time: BigUint::from(time),
+                    distance: BigUint::from(distance),
+                };
+                assert_eq!(big.ways_to_win(), BigUint::from(race.ways_to_win()));
+            }
+        }
+    }
+
+    #[test]
+    fn check_big_race() {
+        // holding for 10^15 exactly ties the record, so the winning holds are just inside it
+        let time = BigUint::from(10_u32).pow(40);
+        let tie = BigUint::from(10_u32).pow(15);
+        let distance = &tie * (&time - &tie);
+
+        // spread the digits out over many columns like the puzzle input
+        let columns = |n: &BigUint, width: usize| {
+            let digits = n.to_string();
+            let columns: Vec<_> = digits.as_bytes().chunks(width).collect();
+            columns
+                .iter()
+                .map(|c| std::str::from_utf8(c).unwrap())
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+        let input = format!(
+            "Time:      {}\nDistance:  {}",
+            columns(&time, 3),
+            columns(&distance, 4)
+        );
+        let race = parse_kerned::<BigUint>(&input);
+        assert_eq!(race.time, time);
+        assert_eq!(
+            race.winning_holds(),
+            Some(&tie + 1_u32..=&time - &tie - 1_u32)
+        );
+        assert_eq!(part2(&input), &time - &tie * 2_u32 - 1_u32);
     }
 }
@@ -1,138 +1,292 @@
-use crate::HandKind::*;
 use std::cmp::Ordering;
-use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::collections::HashMap;
+use std::env;
 
-static CARDS: [char; 13] = [
-    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
-];
+/// A kind of hand, made by groups of cards that share a label.
+#[derive(PartialEq, Eq, Debug)]
+struct HandKind {
+    name: &'static str,
+    // sizes of the groups of matching labels the hand needs, largest first
+    groups: &'static [usize],
+}
 
-static JOKER_CARDS: [char; 13] = [
-    'J', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'Q', 'K', 'A',
-];
+impl HandKind {
+    // a hand has this kind if each group can be matched to a different group in the hand that's
+    // at least as large, pairing the groups off largest to largest is the best way to do that
+    fn matches(&self, groups: &[usize]) -> bool {
+        self.groups.len() <= groups.len() && self.groups.iter().zip(groups).all(|(k, g)| g >= k)
+    }
+}
 
-#[derive(PartialOrd, Ord, PartialEq, Eq, Debug)]
-enum HandKind {
+// the ranking of the five card hands, weakest first
+static KINDS: [HandKind; 7] = [
     // all cards' labels are distinct: 23456
-    HighCard,
+    HandKind {
+        name: "HighCard",
+        groups: &[1],
+    },
     // two cards share one label, and the other three cards have a different label from the pair and each other: A23A4
-    OnePair,
+    HandKind {
+        name: "OnePair",
+        groups: &[2],
+    },
     // two cards share one label, two other cards share a second label, and the remaining card has a third label: 23432
-    TwoPair,
+    HandKind {
+        name: "TwoPair",
+        groups: &[2, 2],
+    },
     // three cards have the same label, and the remaining two cards are each different from any other card in the hand: TTT98
-    ThreeOfAKind,
+    HandKind {
+        name: "ThreeOfAKind",
+        groups: &[3],
+    },
     // three cards have the same label, and the remaining two cards share a different label: 23332
-    FullHouse,
+    HandKind {
+        name: "FullHouse",
+        groups: &[3, 2],
+    },
     // four cards have the same label and one card has a different label: AA8AA
-    FourOfAKind,
+    HandKind {
+        name: "FourOfAKind",
+        groups: &[4],
+    },
     // all five cards have the same label: AAAAA
-    FiveOfAKind,
+    HandKind {
+        name: "FiveOfAKind",
+        groups: &[5],
+    },
+];
+
+/// The rules of a game of Camel Cards.
+#[derive(Debug)]
+struct Ruleset<'a> {
+    // card labels from weakest to strongest
+    order: Vec<char>,
+    // labels that act like whichever label makes the strongest hand
+    wild: Vec<char>,
+    hand_size: usize,
+    // hand kinds from weakest to strongest
+    kinds: &'a [HandKind],
 }
 
-impl HandKind {
-    fn from_string(string: &str, joker: bool) -> Self {
-        let mut cards = HashMap::new();
+impl Ruleset<'static> {
+    fn standard() -> Self {
+        Ruleset {
+            order: "23456789TJQKA".chars().collect(),
+            wild: Vec::new(),
+            hand_size: 5,
+            kinds: &KINDS,
+        }
+    }
+
+    // J cards are jokers, they're wild but they're also the weakest card on their own
+    fn jokers() -> Self {
+        Ruleset {
+            order: "J23456789TQKA".chars().collect(),
+            wild: vec!['J'],
+            hand_size: 5,
+            kinds: &KINDS,
+        }
+    }
+}
+
+impl Ruleset<'_> {
+    fn value(&self, card: char) -> usize {
+        self.order
+            .iter()
+            .position(|c| *c == card)
+            .unwrap_or_else(|| panic!("unknown card {card}"))
+    }
+
+    // index of the strongest kind in the ranking the groups make
+    fn rank(&self, groups: &[usize]) -> usize {
+        self.kinds
+            .iter()
+            .rposition(|k| k.matches(groups))
+            .unwrap_or(0)
+    }
+
+    // the kind takes the top bits of the key, then each card gets enough bits for every label
+    fn key(&self, cards: &str) -> u32 {
+        let bits = |n: usize| usize::BITS - n.saturating_sub(1).leading_zeros();
+        let card_bits = bits(self.order.len());
+        assert!(
+            bits(self.kinds.len()) + card_bits * self.hand_size as u32 <= u32::BITS,
+            "hands don't fit in a u32 key"
+        );
+        cards
+            .chars()
+            .fold(self.classify(cards).kind as u32, |key, card| {
+                key << card_bits | self.value(card) as u32
+            })
+    }
+
+    // the kind a hand makes once its wild cards have copied the best label
+    fn classify(&self, cards: &str) -> Classification {
+        let mut counts: Vec<(char, usize)> = Vec::with_capacity(self.hand_size);
+        let mut wild = 0;
 
         // count the occurrences of each card
-        for card in string.chars() {
-            let count = match cards.entry(card) {
-                Vacant(entry) => entry.insert(0),
-                Occupied(entry) => entry.into_mut(),
-            };
+        for card in cards.chars() {
+            if self.wild.contains(&card) {
+                wild += 1;
+            } else if let Some((_, count)) = counts.iter_mut().find(|(c, _)| *c == card) {
+                *count += 1;
+            } else {
+                counts.push((card, 1));
+            }
+        }
 
-            *count += 1;
+        // largest groups first, and the strongest label first between groups of the same size
+        counts.sort_unstable_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| self.value(b.0).cmp(&self.value(a.0)))
+        });
+
+        if wild == 0 {
+            let groups: Vec<_> = counts.iter().map(|(_, n)| *n).collect();
+            return Classification {
+                kind: self.rank(&groups),
+                substitute: Vec::new(),
+            };
         }
 
-        match cards.len() {
-            5 => {
-                return if joker && cards.contains_key(&'J') {
-                    OnePair
-                } else {
-                    HighCard
-                }
+        // each wild card can top up a group that's already in the hand or start a new group with
+        // a label the hand doesn't have, and rankings that want several groups (like three pairs)
+        // can need the wild cards split between them, so try every way of sharing them out
+        let spare: Vec<char> = self
+            .order
+            .iter()
+            .rev()
+            .filter(|c| !self.wild.contains(c) && counts.iter().all(|(label, _)| label != *c))
+            .copied()
+            .collect();
+        let mut best = None;
+        self.share_wild(&counts, &spare, wild, &mut Vec::new(), &mut best);
+        best.unwrap_or(Classification {
+            kind: self.rank(&[wild]),
+            substitute: Vec::new(),
+        })
+    }
+
+    // give `wild` cards to the groups in `counts` after the ones in `extra` already have theirs,
+    // then make new groups out of the rest, keeping the strongest kind found in `best`
+    fn share_wild(
+        &self,
+        counts: &[(char, usize)],
+        spare: &[char],
+        wild: usize,
+        extra: &mut Vec<usize>,
+        best: &mut Option<Classification>,
+    ) {
+        if extra.len() < counts.len() {
+            // most cards to the largest groups first, so ties keep the simplest substitution
+            for n in (0..=wild).rev() {
+                extra.push(n);
+                self.share_wild(counts, spare, wild - n, extra, best);
+                extra.pop();
             }
-            4 => {
-                return if joker && cards.contains_key(&'J') {
-                    ThreeOfAKind
-                } else {
-                    OnePair
-                };
+            return;
+        }
+
+        for new_groups in partitions(wild, wild) {
+            if new_groups.len() > spare.len() {
+                continue;
             }
-            3 => {
-                let toak = cards.values().into_iter().any(|card| *card == 3);
-                return if joker && cards.contains_key(&'J') {
-                    let jokers = cards.get(&'J').unwrap();
-                    if toak {
-                        FourOfAKind
-                    } else {
-                        if *jokers == 2 {
-                            FourOfAKind
-                        } else {
-                            FullHouse
-                        }
-                    }
-                } else {
-                    if toak {
-                        ThreeOfAKind
-                    } else {
-                        TwoPair
-                    }
-                };
+            let mut groups: Vec<_> = counts
+                .iter()
+                .zip(extra.iter())
+                .map(|((_, n), e)| n + e)
+                .collect();
+            groups.extend(&new_groups);
+            groups.sort_unstable_by(|a, b| b.cmp(a));
+            let kind = self.rank(&groups);
+            if best.as_ref().is_some_and(|b| b.kind >= kind) {
+                continue;
             }
-            2 => {
-                return if joker && cards.contains_key(&'J') {
-                    FiveOfAKind
+
+            let topped_up = counts
+                .iter()
+                .zip(extra.iter())
+                .map(|((label, _), &e)| (*label, e));
+            let substitute = topped_up
+                .chain(spare.iter().copied().zip(new_groups))
+                .flat_map(|(label, n)| std::iter::repeat_n(label, n))
+                .collect();
+            *best = Some(Classification { kind, substitute });
+        }
+    }
+
+    // the hand written out with each wild card replaced by the label it copies
+    fn substitute(&self, cards: &str, substitute: &[char]) -> String {
+        let mut substitute = substitute.iter();
+        cards
+            .chars()
+            .map(|c| {
+                if self.wild.contains(&c) {
+                    *substitute.next().unwrap()
                 } else {
-                    let foak = cards.values().into_iter().any(|card| *card == 4);
-                    if foak {
-                        FourOfAKind
-                    } else {
-                        FullHouse
-                    }
+                    c
                 }
-            }
-            1 => return FiveOfAKind,
-            _ => panic!(),
+            })
+            .collect()
+    }
+}
+
+// every way of writing `n` as a sum of parts no bigger than `max`, largest parts first
+fn partitions(n: usize, max: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for first in (1..=max.min(n)).rev() {
+        for mut rest in partitions(n - first, first) {
+            rest.insert(0, first);
+            all.push(rest);
         }
     }
+    all
+}
+
+/// The kind a hand was ranked as, and the labels its wild cards copied to get there.
+#[derive(Debug, PartialEq)]
+struct Classification {
+    // index of the kind in the ruleset's ranking
+    kind: usize,
+    // the label each wild card copies, in the order they're dealt
+    substitute: Vec<char>,
 }
 
 #[derive(Eq, Debug)]
 struct Hand<'a> {
     cards: &'a str,
     bet: u32,
-    joker: bool,
-    kind: HandKind,
+    // the hand's kind followed by the value of each card, packed so comparing keys ranks hands
+    key: u32,
+    // position in the input, breaks ties between equal hands
+    dealt: usize,
+}
+
+impl<'a> Hand<'a> {
+    fn new(cards: &'a str, bet: u32, dealt: usize, rules: &Ruleset) -> Self {
+        assert_eq!(
+            cards.chars().count(),
+            rules.hand_size,
+            "hand {cards} has the wrong number of cards"
+        );
+        Hand {
+            cards,
+            bet,
+            key: rules.key(cards),
+            dealt,
+        }
+    }
 }
 
+/// Hands are ranked by their kind, and then card by card from the first one. Hands with the
+/// same cards are equal whatever they bet, `parse_input` keeps them in input order.
 impl Ord for Hand<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.kind.cmp(&other.kind) {
-            // If hand kinds are equal, compare their cards
-            Ordering::Equal => {
-                // build HashMap of cards and their values
-                let cards: HashMap<_, _> = if self.joker {
-                    JOKER_CARDS
-                        .into_iter()
-                        .enumerate()
-                        .map(|(v, c)| (c, v))
-                        .collect()
-                } else {
-                    CARDS.into_iter().enumerate().map(|(v, c)| (c, v)).collect()
-                };
-                // compare each card in each hand
-                for (i, card) in self.cards.chars().into_iter().enumerate() {
-                    let other = other.cards.as_bytes()[i] as char;
-                    if card != other {
-                        let c = cards.get(&card).unwrap();
-                        let o = cards.get(&other).unwrap();
-                        return c.cmp(o);
-                    }
-                }
-                Ordering::Equal
-            }
-            _ => self.kind.cmp(&other.kind),
-        }
+        self.key.cmp(&other.key)
     }
 }
 
@@ -144,53 +298,148 @@ impl PartialOrd for Hand<'_> {
 
 impl PartialEq for Hand<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.kind == other.kind && self.cards == self.cards
+        self.key == other.key
     }
 }
 
 fn main() {
     let input = include_str!("input.txt");
+    let args: Vec<_> = env::args().skip(1).collect();
 
     println!("Part 1: {}", part1(input));
+    print_details(input, &Ruleset::standard(), &args);
     println!("Part 2: {}", part2(input));
+    print_details(input, &Ruleset::jokers(), &args);
 }
 
-fn parse_input(input: &str, joker: bool) -> Vec<Hand> {
+// `--explain` lists how each hand was classified, `--report` and `--csv` show how it was ranked
+fn print_details(input: &str, rules: &Ruleset, args: &[String]) {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    if flag("--explain") {
+        print!("{}", explain_hands(input, rules));
+    }
+    if flag("--report") {
+        print!("{}", report_table(&report(input, rules)));
+    }
+    if flag("--csv") {
+        print!("{}", report_csv(&report(input, rules)));
+    }
+}
+
+fn parse_input<'a>(input: &'a str, rules: &Ruleset) -> Vec<Hand<'a>> {
     let mut hands = Vec::new();
-    for line in input.lines() {
+    for (dealt, line) in input.lines().enumerate() {
         let mut iter = line.split_whitespace();
         let (cards, bet) = (
             iter.next().unwrap(),
             iter.next().unwrap().parse::<u32>().unwrap(),
         );
-        let hand = Hand {
-            cards,
-            bet,
-            joker,
-            kind: HandKind::from_string(cards, joker),
-        };
-        match hands.binary_search(&hand) {
-            Err(pos) => hands.insert(pos, hand),
-            _ => {}
-        }
+        hands.push(Hand::new(cards, bet, dealt, rules));
     }
+    // no two hands were dealt in the same place, so equal hands are ranked in the order they were dealt
+    hands.sort_unstable_by_key(|h| (h.key, h.dealt));
     hands
 }
 
-fn part1(input: &str) -> u32 {
-    let hands = parse_input(input, false);
-    hands
-        .iter()
-        .enumerate()
-        .fold(0, |acc, (pos, hand)| acc + hand.bet * (pos as u32 + 1))
+fn winnings(input: &str, rules: &Ruleset) -> u64 {
+    let hands = parse_input(input, rules);
+    hands.iter().enumerate().fold(0, |acc, (pos, hand)| {
+        acc + hand.bet as u64 * (pos as u64 + 1)
+    })
 }
 
-fn part2(input: &str) -> u32 {
-    let hands = parse_input(input, true);
-    hands
-        .iter()
+// one line per hand in rank order, with the kind and what the wild cards were swapped for
+fn explain_hands(input: &str, rules: &Ruleset) -> String {
+    let mut explanation = String::new();
+    for hand in parse_input(input, rules) {
+        let classification = rules.classify(hand.cards);
+        let kind = rules.kinds[classification.kind].name;
+        let line = if classification.substitute.is_empty() {
+            format!("{} {kind}\n", hand.cards)
+        } else {
+            let cards = rules.substitute(hand.cards, &classification.substitute);
+            format!("{} {kind:<12} as {cards}\n", hand.cards)
+        };
+        explanation.push_str(&line);
+    }
+    explanation
+}
+
+/// A hand's place in the ranking and what it contributes to the total winnings.
+#[derive(Debug, PartialEq)]
+struct ReportRow<'a> {
+    rank: usize,
+    cards: &'a str,
+    kind: &'static str,
+    // the hand with its wild cards swapped for the label they copied
+    substituted: Option<String>,
+    bet: u32,
+    winnings: u64,
+}
+
+// one row per hand, in the order `parse_input` ranks them
+fn report<'a>(input: &'a str, rules: &Ruleset) -> Vec<ReportRow<'a>> {
+    parse_input(input, rules)
+        .into_iter()
         .enumerate()
-        .fold(0, |acc, (pos, hand)| acc + hand.bet * (pos as u32 + 1))
+        .map(|(pos, hand)| {
+            let classification = rules.classify(hand.cards);
+            ReportRow {
+                rank: pos + 1,
+                cards: hand.cards,
+                kind: rules.kinds[classification.kind].name,
+                substituted: (!classification.substitute.is_empty())
+                    .then(|| rules.substitute(hand.cards, &classification.substitute)),
+                bet: hand.bet,
+                winnings: hand.bet as u64 * (pos as u64 + 1),
+            }
+        })
+        .collect()
+}
+
+fn report_table(rows: &[ReportRow]) -> String {
+    let mut table = format!(
+        "{:>6}  {:<8}  {:<12}  {:<8}  {:>6}  {:>10}\n",
+        "rank", "hand", "kind", "as", "bid", "winnings"
+    );
+    for row in rows {
+        table.push_str(&format!(
+            "{:>6}  {:<8}  {:<12}  {:<8}  {:>6}  {:>10}\n",
+            row.rank,
+            row.cards,
+            row.kind,
+            row.substituted.as_deref().unwrap_or(""),
+            row.bet,
+            row.winnings
+        ));
+    }
+    let total: u64 = rows.iter().map(|r| r.winnings).sum();
+    table.push_str(&format!("{:>6}  {:>52}\n", "total", total));
+    table
+}
+
+fn report_csv(rows: &[ReportRow]) -> String {
+    let mut csv = String::from("rank,hand,kind,as,bid,winnings\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.rank,
+            row.cards,
+            row.kind,
+            row.substituted.as_deref().unwrap_or(""),
+            row.bet,
+            row.winnings
+        ));
+    }
+    csv
+}
+
+fn part1(input: &str) -> u64 {
+    winnings(input, &Ruleset::standard())
+}
+
+fn part2(input: &str) -> u64 {
+    winnings(input, &Ruleset::jokers())
 }
 
 #[cfg(test)]
@@ -217,4 +466,224 @@ mod tests {
         let result = part2(INPUT);
         assert_eq!(result, 5905)
     }
+
+    #[test]
+    fn check_classify() {
+        let rules = Ruleset::jokers();
+        let kind = |cards| rules.kinds[rules.classify(cards).kind].name;
+        assert_eq!(kind("32T3K"), "OnePair");
+        assert_eq!(kind("KTJJT"), "FourOfAKind");
+        assert_eq!(kind("JJJJJ"), "FiveOfAKind");
+        assert_eq!(kind("2345J"), "OnePair");
+        assert_eq!(kind("2245J"), "ThreeOfAKind");
+        assert_eq!(kind("2255J"), "FullHouse");
+    }
+
+    // a ranking for six card hands that knows about three pairs
+    static SIX: [HandKind; 5] = [
+        HandKind {
+            name: "HighCard",
+            groups: &[1],
+        },
+        HandKind {
+            name: "OnePair",
+            groups: &[2],
+        },
+        HandKind {
+            name: "ThreePair",
+            groups: &[2, 2, 2],
+        },
+        HandKind {
+            name: "DoubleTriple",
+            groups: &[3, 3],
+        },
+        HandKind {
+            name: "SixOfAKind",
+            groups: &[6],
+        },
+    ];
+
+    #[test]
+    fn check_variant() {
+        // six card hands with two wild labels and a ranking that knows about three pairs
+        let rules = Ruleset {
+            order: "*J23456789TQKA".chars().collect(),
+            wild: vec!['*', 'J'],
+            hand_size: 6,
+            kinds: &SIX,
+        };
+        let kind = |cards| rules.kinds[rules.classify(cards).kind].name;
+        assert_eq!(kind("23456A"), "HighCard");
+        assert_eq!(kind("22334K"), "OnePair");
+        assert_eq!(kind("223344"), "ThreePair");
+        assert_eq!(kind("2233J4"), "ThreePair");
+        assert_eq!(kind("222J33"), "DoubleTriple");
+        assert_eq!(kind("2*J222"), "SixOfAKind");
+
+        // the wild cards have to be split between groups to make these
+        assert_eq!(kind("2233J*"), "DoubleTriple");
+        assert_eq!(kind("2234J*"), "ThreePair");
+        assert_eq!(rules.classify("2233J*").substitute, vec!['3', '2']);
+        assert_eq!(
+            explain_hands("2234J* 1", &rules),
+            "2234J* ThreePair    as 223443\n"
+        );
+
+        let input = indoc! {"
+            223344 10
+            23456A 1
+            2*J222 100
+            J23456 20"
+        };
+        assert_eq!(winnings(input, &rules), 1 + 20 * 2 + 10 * 3 + 100 * 4);
+    }
+
+    #[test]
+    fn check_repeated_hands() {
+        let input = indoc! {"
+            32T3K 765
+            T55J5 684
+            KK677 28
+            32T3K 2
+            KTJJT 220
+            QQQJA 483
+            32T3K 10"
+        };
+        let hands = parse_input(input, &Ruleset::standard());
+        assert_eq!(hands.len(), 7);
+        let bets: Vec<_> = hands.iter().map(|h| h.bet).collect();
+        assert_eq!(bets, vec![765, 2, 10, 220, 28, 684, 483]);
+        assert_eq!(hands[0], hands[1]);
+        assert_eq!(hands[1], hands[2]);
+        assert_ne!(hands[2], hands[3]);
+
+        assert_eq!(
+            part1(input),
+            765 + 2 * 2 + 10 * 3 + 220 * 4 + 28 * 5 + 684 * 6 + 483 * 7
+        );
+    }
+
+    #[test]
+    fn check_key_order() {
+        // deal a lot of hands from a simple generator, only using TJQKA so there are plenty of
+        // matching labels and repeated hands
+        let mut seed: u64 = 7;
+        let mut input = String::new();
+        for bet in 0..20_000 {
+            for _ in 0..5 {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                input.push("23456789TJQKA".as_bytes()[(seed >> 33) as usize % 5 + 8] as char);
+            }
+            input.push_str(&format!(" {bet}\n"));
+        }
+
+        for rules in [Ruleset::standard(), Ruleset::jokers()] {
+            // rank the hands by comparing kinds and then card values
+            let mut expected: Vec<_> = input
+                .lines()
+                .map(|line| {
+                    let (cards, bet) = line.split_once(' ').unwrap();
+                    let values: Vec<_> = cards.chars().map(|c| rules.value(c)).collect();
+                    (
+                        rules.classify(cards).kind,
+                        values,
+                        bet.parse::<u32>().unwrap(),
+                    )
+                })
+                .collect();
+            expected.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+            let bets: Vec<_> = parse_input(&input, &rules).iter().map(|h| h.bet).collect();
+            assert_eq!(bets, expected.iter().map(|e| e.2).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn check_explain() {
+        let expected = indoc! {"
+            32T3K OnePair
+            KK677 TwoPair
+            T55J5 FourOfAKind  as T5555
+            QQQJA FourOfAKind  as QQQQA
+            KTJJT FourOfAKind  as KTTTT
+        "};
+        assert_eq!(explain_hands(INPUT, &Ruleset::jokers()), expected);
+        assert_eq!(
+            Ruleset::jokers().classify("JJJJJ"),
+            Classification {
+                kind: 6,
+                substitute: vec!['A'; 5]
+            }
+        );
+    }
+
+    // the best kind from swapping every wild card for any label, each one independently
+    fn exhaustive(rules: &Ruleset, cards: &[char]) -> usize {
+        match cards.iter().position(|c| rules.wild.contains(c)) {
+            Some(i) => rules
+                .order
+                .iter()
+                .filter(|c| !rules.wild.contains(c))
+                .map(|&label| {
+                    let mut cards = cards.to_vec();
+                    cards[i] = label;
+                    exhaustive(rules, &cards)
+                })
+                .max()
+                .unwrap(),
+            None => rules.classify(&cards.iter().collect::<String>()).kind,
+        }
+    }
+
+    #[test]
+    fn check_classify_exhaustive() {
+        let labels = ['2', '3', '4', 'J', '*'];
+        for (hand_size, kinds) in (1..=6).flat_map(|n| [(n, &KINDS[..]), (n, &SIX[..])]) {
+            let rules = Ruleset {
+                order: labels.to_vec(),
+                wild: vec!['J', '*'],
+                hand_size,
+                kinds,
+            };
+            // every hand of this size made from the labels
+            for n in 0..labels.len().pow(hand_size as u32) {
+                let cards: Vec<_> = (0..hand_size)
+                    .map(|i| labels[n / labels.len().pow(i as u32) % labels.len()])
+                    .collect();
+                let hand: String = cards.iter().collect();
+                assert_eq!(
+                    rules.classify(&hand).kind,
+                    exhaustive(&rules, &cards),
+                    "{hand}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn check_report() {
+        let rows = report(INPUT, &Ruleset::jokers());
+        assert_eq!(rows.iter().map(|r| r.winnings).sum::<u64>(), part2(INPUT));
+
+        let expected = indoc! {"
+            rank,hand,kind,as,bid,winnings
+            1,32T3K,OnePair,,765,765
+            2,KK677,TwoPair,,28,56
+            3,T55J5,FourOfAKind,T5555,684,2052
+            4,QQQJA,FourOfAKind,QQQQA,483,1932
+            5,KTJJT,FourOfAKind,KTTTT,220,1100
+        "};
+        assert_eq!(report_csv(&rows), expected);
+
+        let table = report_table(&rows);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(
+            lines[3],
+            "     3  T55J5     FourOfAKind   T5555        684        2052"
+        );
+        assert!(lines[6].starts_with(" total") && lines[6].ends_with(" 5905"));
+    }
 }
@@ -0,0 +1,208 @@
+use crate::navigate::{Route, Selector};
+use crate::network::{Direction, Network};
+use crate::traversal::Cycle;
+use std::collections::HashSet;
+
+const DIRECTIONS: [Direction; 2] = [Direction::Left, Direction::Right];
+
+/// Walks drawn on top of an exported network, as the edges they take.
+///
+/// An edge is a node and the direction followed out of it.
+#[derive(Debug, Default)]
+pub struct Overlay {
+    pub path: HashSet<(u32, Direction)>,
+    pub cycles: HashSet<(u32, Direction)>,
+}
+
+impl Overlay {
+    /// Add the edges a route takes from its start to its goal.
+    pub fn add_route(&mut self, network: &Network, route: &Route) {
+        let steps = route.path[..route.path.len() - 1].iter().enumerate();
+        self.path
+            .extend(steps.map(|(step, &node)| (node, instruction(network, step))));
+    }
+
+    /// Add the edges a walk keeps going around once it starts repeating itself.
+    pub fn add_cycle(&mut self, network: &Network, cycle: &Cycle) {
+        let steps = cycle.path.iter().enumerate().skip(cycle.start as usize);
+        self.cycles
+            .extend(steps.map(|(step, &node)| (node, instruction(network, step))));
+    }
+}
+
+// the direction followed on a step of a walk
+fn instruction(network: &Network, step: usize) -> Direction {
+    network.directions[step % network.directions.len()]
+}
+
+// a node name as a quoted DOT ID, names like `11A` aren't valid as bare IDs
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The network as a Graphviz digraph.
+///
+/// Start nodes are filled green, goal nodes red and nodes that are both gold. Edges are
+/// labelled with their direction, those on the overlaid path are blue and those on the
+/// overlaid cycles are bold and orange.
+pub fn to_dot(network: &Network, start: &Selector, goal: &Selector, overlay: &Overlay) -> String {
+    let mut dot = String::from("digraph network {\n");
+    for node in network.nodes() {
+        let name = network.name(node);
+        let fill = match (start.matches(name), goal.matches(name)) {
+            (true, true) => "gold",
+            (true, false) => "palegreen",
+            (false, true) => "lightcoral",
+            (false, false) => continue,
+        };
+        dot.push_str(&format!(
+            "    {} [style=filled, fillcolor={fill}];\n",
+            dot_id(name)
+        ));
+    }
+
+    for node in network.nodes() {
+        for direction in DIRECTIONS {
+            let edge = (node, direction);
+            let mut attributes = vec![format!("label=\"{}\"", direction.label())];
+            match (overlay.path.contains(&edge), overlay.cycles.contains(&edge)) {
+                (true, true) => attributes.push("color=\"blue:orange\", style=bold".to_string()),
+                (true, false) => attributes.push("color=blue".to_string()),
+                (false, true) => attributes.push("color=orange, style=bold".to_string()),
+                (false, false) => {}
+            }
+            dot.push_str(&format!(
+                "    {} -> {} [{}];\n",
+                dot_id(network.name(node)),
+                dot_id(network.name(network.step(node, direction))),
+                attributes.join(", ")
+            ));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// the directions of a node's edges that are in `edges`, as a JSON array
+fn json_directions(node: u32, edges: &HashSet<(u32, Direction)>) -> String {
+    let labels: Vec<_> = DIRECTIONS
+        .iter()
+        .filter(|&&d| edges.contains(&(node, d)))
+        .map(|d| format!("\"{}\"", d.label()))
+        .collect();
+    format!("[{}]", labels.join(", "))
+}
+
+/// The network as a JSON adjacency list, one node per line.
+///
+/// Node names are only ever letters and digits so they don't need escaping. `path` and
+/// `cycle` list the directions out of each node the overlay takes.
+pub fn to_json(network: &Network, start: &Selector, goal: &Selector, overlay: &Overlay) -> String {
+    let directions: String = network.directions.iter().map(|d| d.label()).collect();
+    let nodes: Vec<_> = network
+        .nodes()
+        .map(|node| {
+            let name = network.name(node);
+            format!(
+                "    {{\"name\": \"{name}\", \"left\": \"{}\", \"right\": \"{}\", \"start\": {}, \"goal\": {}, \"path\": {}, \"cycle\": {}}}",
+                network.name(network.step(node, Direction::Left)),
+                network.name(network.step(node, Direction::Right)),
+                start.matches(name),
+                goal.matches(name),
+                json_directions(node, &overlay.path),
+                json_directions(node, &overlay.cycles),
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"directions\": \"{directions}\",\n  \"nodes\": [\n{}\n  ]\n}}\n",
+        nodes.join(",\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::navigate;
+    use indoc::indoc;
+
+    static INPUT: &str = indoc! {"
+        LR
+
+        AAA = (BBB, AAA)
+        BBB = (AAA, ZZZ)
+        ZZZ = (ZZZ, ZZZ)"
+    };
+
+    #[test]
+    fn check_dot() {
+        let network = Network::parse(INPUT).unwrap();
+        let (start, goal) = (Selector::names(["AAA"]), Selector::names(["ZZZ"]));
+        let mut overlay = Overlay::default();
+        for route in navigate::navigate(&network, &start, &goal).unwrap() {
+            overlay.add_route(&network, &route);
+        }
+        // once AAA's walk reaches ZZZ it stays there
+        let is_goal = vec![false, false, true];
+        overlay.add_cycle(&network, &Cycle::analyze(&network, 0, &is_goal));
+
+        assert_eq!(
+            to_dot(&network, &start, &goal, &overlay),
+            indoc! {r#"
+                digraph network {
+                    "AAA" [style=filled, fillcolor=palegreen];
+                    "ZZZ" [style=filled, fillcolor=lightcoral];
+                    "AAA" -> "BBB" [label="L", color=blue];
+                    "AAA" -> "AAA" [label="R"];
+                    "BBB" -> "AAA" [label="L"];
+                    "BBB" -> "ZZZ" [label="R", color=blue];
+                    "ZZZ" -> "ZZZ" [label="L", color=orange, style=bold];
+                    "ZZZ" -> "ZZZ" [label="R", color=orange, style=bold];
+                }
+            "#}
+        );
+    }
+
+    #[test]
+    fn check_dot_ids() {
+        // names starting with a digit have to be quoted to be valid DOT
+        let network = Network::parse(indoc! {"
+            L
+
+            11A = (11Z, 11A)
+            11Z = (11Z, 11Z)"
+        })
+        .unwrap();
+        let (start, goal) = (Selector::names(["11A"]), Selector::names(["11Z"]));
+        let dot = to_dot(&network, &start, &goal, &Overlay::default());
+        assert!(dot.contains("    \"11A\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(dot.contains("    \"11A\" -> \"11Z\" [label=\"L\"];\n"));
+
+        assert_eq!(dot_id("11A"), "\"11A\"");
+        assert_eq!(dot_id(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn check_json() {
+        let network = Network::parse(INPUT).unwrap();
+        let (start, goal) = (Selector::names(["AAA"]), Selector::names(["ZZZ"]));
+        let mut overlay = Overlay::default();
+        for route in navigate::navigate(&network, &start, &goal).unwrap() {
+            overlay.add_route(&network, &route);
+        }
+
+        assert_eq!(
+            to_json(&network, &start, &goal, &overlay),
+            indoc! {r#"
+                {
+                  "directions": "LR",
+                  "nodes": [
+                    {"name": "AAA", "left": "BBB", "right": "AAA", "start": true, "goal": false, "path": ["L"], "cycle": []},
+                    {"name": "BBB", "left": "AAA", "right": "ZZZ", "start": false, "goal": false, "path": ["R"], "cycle": []},
+                    {"name": "ZZZ", "left": "ZZZ", "right": "ZZZ", "start": false, "goal": true, "path": [], "cycle": []}
+                  ]
+                }
+            "#}
+        );
+    }
+}
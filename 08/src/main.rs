@@ -1,126 +1,113 @@
-use regex::Regex;
-use std::cmp;
-use std::collections::{HashMap, VecDeque};
+use export::Overlay;
+use navigate::Selector;
+use network::Network;
+use std::env;
+use traversal::Cycle;
+
+mod export;
+mod navigate;
+mod network;
+mod traversal;
 
 fn main() {
     let input = include_str!("input.txt");
+    let args: Vec<_> = env::args().skip(1).collect();
 
     println!("Part 1: {}", part1(input));
     println!("Part 2: {}", part2(input));
+    print_routes(input, &args);
+    print_export(input, &args);
 }
 
-fn part1(input: &str) -> u32 {
-    // sections are separated by empty lines
-    let re = Regex::new(r"(?m)\n\n").unwrap();
-    let mut iter = re.split(input);
-    let mut directions = VecDeque::from_iter(iter.next().unwrap().chars());
-
-    let mut tree = HashMap::new();
-    let re = Regex::new(r"([A-Z]+) = \(([A-Z]+), ([A-Z]+)\)").unwrap();
-    for line in iter.next().unwrap().lines() {
-        let caps = re.captures(line).unwrap();
-        let (key, (left, right)) = (
-            caps.get(1).unwrap().as_str(),
-            (caps.get(2).unwrap().as_str(), caps.get(3).unwrap().as_str()),
-        );
-        tree.insert(key, (left, right));
-    }
+// the value following a command line option
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    let value = args.get(position + 1).map(String::as_str);
+    Some(value.unwrap_or_else(|| panic!("{name} needs a value")))
+}
 
-    let mut steps = 0;
-    let mut current = "AAA";
-    while current != "ZZZ" {
-        // get current direction
-        let direction = directions.pop_front().unwrap();
-        directions.push_back(direction);
-
-        // increase steps taken
-        steps += 1;
-
-        // update current node with the next in the tree based on the directions
-        current = match direction {
-            'L' => tree.get(&current).unwrap().0,
-            'R' => tree.get(&current).unwrap().1,
-            _ => panic!(), // this should never happen
-        };
-    }
-    steps
+fn selector(pattern: &str) -> Selector<'static> {
+    Selector::pattern(pattern).unwrap_or_else(|e| panic!("{e}"))
 }
 
-// least common multiple
-fn lcm(first: u64, second: u64) -> u64 {
-    first * second / gcd(first, second)
+// `--from PATTERN --to PATTERN` shows the route from every matching start node to its first goal
+fn print_routes(input: &str, args: &[String]) {
+    let (Some(from), Some(to)) = (option(args, "--from"), option(args, "--to")) else {
+        return;
+    };
+
+    let network = parse_input(input);
+    let routes = navigate::navigate(&network, &selector(from), &selector(to));
+    for route in routes.unwrap_or_else(|e| panic!("{e}")) {
+        let path: Vec<_> = route.path.iter().map(|&n| network.name(n)).collect();
+        println!("{} steps: {}", route.steps, path.join(" -> "));
+    }
 }
 
-// greatest common divisor
-fn gcd(first: u64, second: u64) -> u64 {
-    let mut max = cmp::max(first, second);
-    let mut min = cmp::min(first, second);
+// `--dot` and `--json` export the network with the ghosts' starts and goals highlighted, or the
+// ones picked by `--from` and `--to`, and `--overlay path` or `--overlay cycles` draws their walks
+fn print_export(input: &str, args: &[String]) {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    if !flag("--dot") && !flag("--json") {
+        return;
+    }
 
-    loop {
-        let res = max % min;
-        if res == 0 {
-            return min;
+    let network = parse_input(input);
+    let start = selector(option(args, "--from").unwrap_or("A$"));
+    let goal = selector(option(args, "--to").unwrap_or("Z$"));
+    let mut overlay = Overlay::default();
+    match option(args, "--overlay") {
+        Some("path") => {
+            let routes = navigate::navigate(&network, &start, &goal);
+            for route in routes.unwrap_or_else(|e| panic!("{e}")) {
+                overlay.add_route(&network, &route);
+            }
         }
+        Some("cycles") => {
+            let is_goal: Vec<_> = network
+                .nodes()
+                .map(|n| goal.matches(network.name(n)))
+                .collect();
+            for node in start.select(&network) {
+                overlay.add_cycle(&network, &Cycle::analyze(&network, node, &is_goal));
+            }
+        }
+        Some(other) => panic!("unknown overlay {other}, expected path or cycles"),
+        None => {}
+    }
 
-        max = min;
-        min = res;
+    if flag("--dot") {
+        print!("{}", export::to_dot(&network, &start, &goal, &overlay));
+    }
+    if flag("--json") {
+        print!("{}", export::to_json(&network, &start, &goal, &overlay));
     }
 }
 
-fn part2(input: &str) -> u64 {
-    // sections are separated by empty lines
-    let re = Regex::new(r"(?m)\n\n").unwrap();
-    let mut iter = re.split(input);
-    let mut directions = VecDeque::from_iter(iter.next().unwrap().chars());
-
-    let mut tree = HashMap::new();
-    let mut current = Vec::new();
-    let re = Regex::new(r"([A-Z0-9]+) = \(([A-Z0-9]+), ([A-Z0-9]+)\)").unwrap();
-    for line in iter.next().unwrap().lines() {
-        let caps = re.captures(line).unwrap();
-        let (key, (left, right)) = (
-            caps.get(1).unwrap().as_str(),
-            (caps.get(2).unwrap().as_str(), caps.get(3).unwrap().as_str()),
-        );
-        tree.insert(key, (left, right));
-        if key.ends_with('A') {
-            current.push(key);
-        }
-    }
+fn parse_input(input: &str) -> Network<'_> {
+    Network::parse(input).unwrap_or_else(|e| panic!("{e}"))
+}
 
-    let mut mutiples = vec![];
-    let mut total_steps = 0;
-    while current.len() > 0 {
-        // Get current direction.
-        let direction = directions.pop_front().unwrap();
-        directions.push_back(direction);
-
-        // Increase steps taken.
-        total_steps += 1;
-
-        // Map each current node to their next positions, removing any that end with 'Z'
-        // If any end with 'Z', we make a note of the current
-        // step so we can determine the least common multiple later
-        let (next, found_z): (Vec<_>, Vec<_>) = current
-            .clone()
-            .into_iter()
-            .map(|e| match direction {
-                'L' => tree.get(e).unwrap().0,
-                'R' => tree.get(e).unwrap().1,
-                _ => panic!(), // this should never happen
-            })
-            .partition(|&e| !e.ends_with('Z'));
-
-        // Assign the filtered nodes to current.
-        current = next;
-
-        // If any nodes are found that end with 'Z' we make a note of the current step so we can determine the least common multiple later.
-        if found_z.len() > 0 {
-            mutiples.push(total_steps);
-        }
-    }
+fn part1(input: &str) -> u64 {
+    let network = parse_input(input);
+    let routes = navigate::navigate(
+        &network,
+        &Selector::names(["AAA"]),
+        &Selector::names(["ZZZ"]),
+    );
+    let route = routes.unwrap_or_else(|e| panic!("{e}")).into_iter().next();
+    route
+        .unwrap_or_else(|| panic!("there is no AAA node to start from"))
+        .steps
+}
 
-    mutiples.into_iter().reduce(|acc, e| lcm(acc, e)).unwrap()
+fn part2(input: &str) -> u64 {
+    let network = parse_input(input);
+    let start = Selector::predicate(|name| name.ends_with('A'));
+    let goal = Selector::predicate(|name| name.ends_with('Z'));
+
+    // every ghost's path ends in a loop, line the goals in those loops up with each other
+    navigate::meet(&network, &start, &goal).unwrap_or_else(|e| panic!("{e}"))
 }
 
 #[cfg(test)]
@@ -158,4 +145,29 @@ mod tests {
         let result = part2(input);
         assert_eq!(result, 6)
     }
+
+    #[test]
+    #[should_panic(expected = "no goal is reachable from AAA, it loops through AAA -> BBB")]
+    fn check_part1_unreachable() {
+        let input = indoc! {"
+            L
+
+            AAA = (BBB, ZZZ)
+            BBB = (AAA, ZZZ)
+            ZZZ = (ZZZ, ZZZ)"
+        };
+        part1(input);
+    }
+
+    #[test]
+    #[should_panic(expected = "there is no AAA node to start from")]
+    fn check_part1_no_start() {
+        let input = indoc! {"
+            L
+
+            BBB = (ZZZ, ZZZ)
+            ZZZ = (ZZZ, ZZZ)"
+        };
+        part1(input);
+    }
 }
@@ -0,0 +1,190 @@
+use crate::network::Network;
+use crate::traversal::{self, Cycle, TraversalError};
+use regex::Regex;
+use std::collections::HashSet;
+
+/// Picks out the nodes a walk starts from or is trying to reach.
+pub enum Selector<'f> {
+    Names(HashSet<String>),
+    Pattern(Regex),
+    Predicate(Box<dyn Fn(&str) -> bool + 'f>),
+}
+
+impl<'f> Selector<'f> {
+    pub fn names<'n>(names: impl IntoIterator<Item = &'n str>) -> Selector<'f> {
+        Selector::Names(names.into_iter().map(str::to_string).collect())
+    }
+
+    pub fn pattern(pattern: &str) -> Result<Selector<'f>, regex::Error> {
+        Regex::new(pattern).map(Selector::Pattern)
+    }
+
+    pub fn predicate(predicate: impl Fn(&str) -> bool + 'f) -> Selector<'f> {
+        Selector::Predicate(Box::new(predicate))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Selector::Names(names) => names.contains(name),
+            Selector::Pattern(pattern) => pattern.is_match(name),
+            Selector::Predicate(predicate) => predicate(name),
+        }
+    }
+
+    /// The selected nodes, in the order they were first seen.
+    pub fn select(&self, network: &Network) -> Vec<u32> {
+        match self {
+            // look the names up instead of checking every node, indices are in the order seen
+            Selector::Names(names) => {
+                let mut nodes: Vec<_> = names.iter().filter_map(|n| network.node(n)).collect();
+                nodes.sort_unstable();
+                nodes
+            }
+            _ => network
+                .nodes()
+                .filter(|&n| self.matches(network.name(n)))
+                .collect(),
+        }
+    }
+
+    // whether each node is selected, indexed by node
+    fn mask(&self, network: &Network) -> Vec<bool> {
+        network
+            .nodes()
+            .map(|n| self.matches(network.name(n)))
+            .collect()
+    }
+}
+
+/// A walk from one start node to the first goal it reaches.
+#[derive(Debug, PartialEq)]
+pub struct Route {
+    pub steps: u64,
+    // every node on the way, from the start to the goal
+    pub path: Vec<u32>,
+}
+
+/// The route from each selected start node to the first goal it reaches.
+///
+/// Fails with `Unreachable` for the first start node that loops without reaching a goal.
+pub fn navigate(
+    network: &Network,
+    start: &Selector,
+    goal: &Selector,
+) -> Result<Vec<Route>, TraversalError> {
+    let is_goal = goal.mask(network);
+    start
+        .select(network)
+        .into_iter()
+        .map(|node| {
+            // a walk's first goal comes before it starts repeating itself
+            let mut cycle = Cycle::analyze(network, node, &is_goal);
+            let Some(&steps) = cycle.hits.first() else {
+                return Err(cycle.unreachable(network));
+            };
+            cycle.path.truncate(steps as usize + 1);
+            Ok(Route {
+                steps,
+                path: cycle.path,
+            })
+        })
+        .collect()
+}
+
+/// The first step where walks from every selected start node are on goals at the same time.
+///
+/// The walks can be far too long to keep their paths, so only the step count is returned.
+pub fn meet(network: &Network, start: &Selector, goal: &Selector) -> Result<u64, TraversalError> {
+    traversal::first_meeting(network, &start.select(network), &goal.mask(network))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    static INPUT: &str = indoc! {"
+        LR
+
+        11A = (11B, XXX)
+        11B = (XXX, 11Z)
+        11Z = (11B, XXX)
+        22A = (22B, XXX)
+        22B = (22C, 22C)
+        22C = (22Z, 22Z)
+        22Z = (22B, 22B)
+        XXX = (XXX, XXX)"
+    };
+
+    fn names(network: &Network, path: &[u32]) -> Vec<String> {
+        path.iter().map(|&n| network.name(n).to_string()).collect()
+    }
+
+    #[test]
+    fn check_selectors() {
+        let network = Network::parse(INPUT).unwrap();
+        let select = |selector: Selector| names(&network, &selector.select(&network));
+        assert_eq!(select(Selector::names(["22C", "11A"])), ["11A", "22C"]);
+        assert!(select(Selector::names(["AAA"])).is_empty());
+        assert_eq!(
+            select(Selector::pattern("^22[AZ]$").unwrap()),
+            ["22A", "22Z"]
+        );
+        assert_eq!(select(Selector::predicate(|n| n.starts_with('X'))), ["XXX"]);
+        assert!(Selector::pattern("(").is_err());
+    }
+
+    #[test]
+    fn check_navigate() {
+        let network = Network::parse(INPUT).unwrap();
+        let routes = navigate(
+            &network,
+            &Selector::predicate(|n| n.ends_with('A')),
+            &Selector::pattern("Z$").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(routes.iter().map(|r| r.steps).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(names(&network, &routes[0].path), ["11A", "11B", "11Z"]);
+        assert_eq!(
+            names(&network, &routes[1].path),
+            ["22A", "22B", "22C", "22Z"]
+        );
+        assert_eq!(network.name(*routes[1].path.last().unwrap()), "22Z");
+
+        // a start node that's already a goal doesn't have to go anywhere
+        let routes = navigate(
+            &network,
+            &Selector::names(["22C"]),
+            &Selector::names(["22C"]),
+        );
+        assert_eq!(routes.unwrap()[0].path, [network.node("22C").unwrap()]);
+
+        // XXX only leads back to itself
+        let error = navigate(
+            &network,
+            &Selector::names(["11A", "XXX"]),
+            &Selector::names(["11Z"]),
+        );
+        assert_eq!(
+            error,
+            Err(TraversalError::Unreachable {
+                start: "XXX".to_string(),
+                cycle: vec!["XXX".to_string(), "XXX".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn check_meet() {
+        let network = Network::parse(INPUT).unwrap();
+        let start = Selector::pattern("A$").unwrap();
+        assert_eq!(
+            meet(&network, &start, &Selector::pattern("Z$").unwrap()),
+            Ok(6)
+        );
+        assert_eq!(
+            meet(&network, &start, &Selector::names(["11Z", "22C"])),
+            Ok(2)
+        );
+    }
+}
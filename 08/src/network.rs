@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The letter the direction is written as in the instructions.
+    pub fn label(self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        }
+    }
+}
+
+// split `AAA = (BBB, CCC)` into its three names
+fn parse_node(line: &str) -> Option<(&str, &str, &str)> {
+    let (node, neighbours) = line.split_once(" = ")?;
+    let (left, right) = neighbours
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(", ")?;
+    let valid = |name: &str| !name.is_empty() && name.chars().all(char::is_alphanumeric);
+    [node, left, right]
+        .iter()
+        .all(|n| valid(n))
+        .then_some((node, left, right))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NetworkError {
+    Malformed(String),
+    NoDirections,
+    Direction(char),
+    Duplicate(String),
+    Undefined(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::Malformed(line) => write!(f, "malformed node: {line}"),
+            NetworkError::NoDirections => write!(f, "there are no directions to follow"),
+            NetworkError::Direction(c) => write!(f, "unknown direction {c}"),
+            NetworkError::Duplicate(name) => write!(f, "node {name} is defined twice"),
+            NetworkError::Undefined(name) => write!(f, "node {name} is never defined"),
+        }
+    }
+}
+
+/// The instructions and the left/right network of nodes they're followed through.
+///
+/// Node names are interned into dense `u32` indices, `left` and `right` hold the
+/// index of each node's neighbours so following an instruction is an array lookup.
+#[derive(Debug)]
+pub struct Network<'a> {
+    pub directions: Vec<Direction>,
+    names: Vec<&'a str>,
+    indices: HashMap<&'a str, u32>,
+    left: Vec<u32>,
+    right: Vec<u32>,
+}
+
+impl<'a> Network<'a> {
+    pub fn parse(input: &'a str) -> Result<Network<'a>, NetworkError> {
+        // sections are separated by empty lines
+        let (directions, nodes) = input
+            .split_once("\n\n")
+            .ok_or_else(|| NetworkError::Malformed(input.to_string()))?;
+
+        let directions: Vec<_> = directions
+            .trim()
+            .chars()
+            .map(|c| match c {
+                'L' => Ok(Direction::Left),
+                'R' => Ok(Direction::Right),
+                _ => Err(NetworkError::Direction(c)),
+            })
+            .collect::<Result<_, _>>()?;
+        // without directions there's no way to walk anywhere
+        if directions.is_empty() {
+            return Err(NetworkError::NoDirections);
+        }
+
+        let mut network = Network {
+            directions,
+            names: Vec::new(),
+            indices: HashMap::new(),
+            left: Vec::new(),
+            right: Vec::new(),
+        };
+
+        // nodes can be used before they're defined, so intern every name as it's seen and
+        // check they were all defined at the end
+        let mut defined = Vec::new();
+        for line in nodes.lines().filter(|l| !l.trim().is_empty()) {
+            let (node, left, right) =
+                parse_node(line.trim()).ok_or_else(|| NetworkError::Malformed(line.to_string()))?;
+            let (node, left, right) = (
+                network.intern(node),
+                network.intern(left),
+                network.intern(right),
+            );
+            defined.resize(network.names.len(), false);
+            if defined[node as usize] {
+                return Err(NetworkError::Duplicate(network.name(node).to_string()));
+            }
+            defined[node as usize] = true;
+            network.left[node as usize] = left;
+            network.right[node as usize] = right;
+        }
+
+        defined.resize(network.names.len(), false);
+        if let Some(node) = defined.iter().position(|d| !d) {
+            return Err(NetworkError::Undefined(network.names[node].to_string()));
+        }
+
+        Ok(network)
+    }
+
+    fn intern(&mut self, name: &'a str) -> u32 {
+        if let Some(&node) = self.indices.get(name) {
+            return node;
+        }
+        let node = self.names.len() as u32;
+        self.names.push(name);
+        self.indices.insert(name, node);
+        self.left.push(node);
+        self.right.push(node);
+        node
+    }
+
+    /// Every node's index, in the order they were first seen.
+    pub fn nodes(&self) -> Range<u32> {
+        0..self.names.len() as u32
+    }
+
+    pub fn node(&self, name: &str) -> Option<u32> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, node: u32) -> &'a str {
+        self.names[node as usize]
+    }
+
+    pub fn step(&self, node: u32, direction: Direction) -> u32 {
+        match direction {
+            Direction::Left => self.left[node as usize],
+            Direction::Right => self.right[node as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn check_parse() {
+        let network = Network::parse(indoc! {"
+            LR
+
+            start = (a1, Ärger)
+            a1 = (start, a1)
+            Ärger = (Ärger, Ärger)"
+        })
+        .unwrap();
+        assert_eq!(network.directions, vec![Direction::Left, Direction::Right]);
+        assert_eq!(network.nodes().len(), 3);
+
+        let start = network.node("start").unwrap();
+        let a1 = network.step(start, Direction::Left);
+        assert_eq!(network.name(a1), "a1");
+        assert_eq!(network.name(network.step(start, Direction::Right)), "Ärger");
+        assert_eq!(network.step(a1, Direction::Left), start);
+        assert_eq!(network.node("missing"), None);
+    }
+
+    #[test]
+    fn check_parse_errors() {
+        let undefined = "L\n\nAAA = (BBB, AAA)";
+        assert_eq!(
+            Network::parse(undefined).unwrap_err(),
+            NetworkError::Undefined("BBB".to_string())
+        );
+        let duplicate = "L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)";
+        assert_eq!(
+            Network::parse(duplicate).unwrap_err(),
+            NetworkError::Duplicate("AAA".to_string())
+        );
+        assert_eq!(
+            Network::parse("LX\n\nAAA = (AAA, AAA)").unwrap_err(),
+            NetworkError::Direction('X')
+        );
+        assert_eq!(
+            Network::parse("\n\nAAA = (AAA, AAA)").unwrap_err(),
+            NetworkError::NoDirections
+        );
+        assert_eq!(
+            Network::parse("L\n\nAAA = (A-A, AAA)").unwrap_err(),
+            NetworkError::Malformed("AAA = (A-A, AAA)".to_string())
+        );
+    }
+}
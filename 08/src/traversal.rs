@@ -0,0 +1,528 @@
+use crate::network::Network;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum TraversalError {
+    /// The walk from `start` repeats a state without reaching a goal, it goes around the
+    /// nodes in `cycle` forever.
+    Unreachable { start: String, cycle: Vec<String> },
+    /// Every walk keeps reaching goals, but never all at the same step.
+    Apart,
+    /// The walks can only be on goals together after more steps than a `u64` can count.
+    TooFar,
+}
+
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraversalError::Unreachable { start, cycle } => write!(
+                f,
+                "no goal is reachable from {start}, it loops through {}",
+                cycle.join(" -> ")
+            ),
+            TraversalError::Apart => write!(f, "the walks are never on goals at the same time"),
+            TraversalError::TooFar => {
+                write!(
+                    f,
+                    "the walks can't be on goals together within {} steps",
+                    u64::MAX
+                )
+            }
+        }
+    }
+}
+
+/// Where a ghost's walk through the network ends up repeating itself.
+///
+/// A ghost's state is its node and its position in the instructions, so after at
+/// most `nodes * instructions` steps it repeats a state and loops forever. `start`
+/// is the first step inside the loop and `length` is how many steps go around it.
+#[derive(Debug, PartialEq)]
+pub struct Cycle {
+    pub start: u64,
+    pub length: u64,
+    // every step before `start + length` the ghost is on a goal
+    pub hits: Vec<u64>,
+    // the node the ghost is on at every step before `start + length`
+    pub path: Vec<u32>,
+}
+
+impl Cycle {
+    pub fn analyze(network: &Network, node: u32, is_goal: &[bool]) -> Cycle {
+        let instructions = network.directions.len();
+        let mut seen: HashMap<(u32, usize), u64> = HashMap::new();
+        let mut hits = Vec::new();
+        let mut path = Vec::new();
+
+        let mut current = node;
+        for step in 0.. {
+            let i = step as usize % instructions;
+            if let Some(&start) = seen.get(&(current, i)) {
+                return Cycle {
+                    start,
+                    length: step - start,
+                    hits,
+                    path,
+                };
+            }
+            seen.insert((current, i), step);
+            path.push(current);
+            if is_goal[current as usize] {
+                hits.push(step);
+            }
+            current = network.step(current, network.directions[i]);
+        }
+        unreachable!()
+    }
+
+    // true if the ghost is on a goal after `step` steps
+    pub fn hits(&self, step: u64) -> bool {
+        let step = if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        };
+        self.hits.binary_search(&step).is_ok()
+    }
+
+    // the walk never reaches a goal once it's looping
+    pub fn unreachable(&self, network: &Network) -> TraversalError {
+        let name = |&n: &u32| network.name(n).to_string();
+        TraversalError::Unreachable {
+            start: name(&self.path[0]),
+            cycle: self.path[self.start as usize..].iter().map(name).collect(),
+        }
+    }
+
+    // the goals the ghost keeps coming back to, as steps modulo the length of the cycle
+    fn residues(&self) -> Vec<u64> {
+        self.hits
+            .iter()
+            .filter(|&&h| h >= self.start)
+            .map(|h| h % self.length)
+            .collect()
+    }
+}
+
+// greatest common divisor, and coefficients x, y where a * x + b * y = gcd
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Combine `t ≡ a (mod n)` and `t ≡ b (mod m)` into a single congruence, the moduli don't need
+/// to be coprime. There's no solution if the residues disagree on the moduli's common factor.
+///
+/// The moduli are `u64`s so the combined modulus and every product along the way fit a `u128`.
+pub fn crt((a, n): (u64, u64), (b, m): (u64, u64)) -> Option<(u128, u128)> {
+    let (g, x, _) = extended_gcd(n as i128, m as i128);
+    let diff = b as i128 - a as i128;
+    if diff % g != 0 {
+        return None;
+    }
+
+    // t = a + n * k where n * k ≡ b - a (mod m), so k ≡ x * (b - a) / g (mod m / g)
+    let step = m as i128 / g;
+    let k = x.rem_euclid(step) as u128 * (diff / g).rem_euclid(step) as u128 % step as u128;
+    let lcm = (n / g as u64) as u128 * m as u128;
+    Some(((a as u128 + n as u128 * k) % lcm, lcm))
+}
+
+// give up on combining residues when there are more combinations than this
+const MAX_RESIDUES: usize = 1 << 16;
+
+/// The first step where every ghost is on a goal at the same time.
+///
+/// Fails with `Unreachable` if some ghost ends up looping without passing a goal, and with
+/// `TooFar` if the ghosts can't meet within `u64::MAX` steps. Uses the residues of each cycle
+/// and the Chinese Remainder Theorem, and falls back to checking every goal of the ghost with
+/// the longest cycle when there are too many combinations of goals to keep.
+pub fn first_meeting(
+    network: &Network,
+    starts: &[u32],
+    is_goal: &[bool],
+) -> Result<u64, TraversalError> {
+    let cycles: Vec<_> = starts
+        .iter()
+        .map(|&s| Cycle::analyze(network, s, is_goal))
+        .collect();
+    let loops_from = cycles.iter().map(|c| c.start).max().unwrap_or(0);
+
+    // before every ghost is looping, at least one of them is still on its way in, so the only
+    // candidates are the goals that ghost passes on the way
+    let mut early: Vec<_> = cycles
+        .iter()
+        .flat_map(|c| c.hits.iter().copied().filter(|&h| h < c.start))
+        .collect();
+    early.sort_unstable();
+    if let Some(step) = early
+        .into_iter()
+        .find(|&s| cycles.iter().all(|c| c.hits(s)))
+    {
+        return Ok(step);
+    }
+    if let Some(cycle) = cycles.iter().find(|c| c.residues().is_empty()) {
+        return Err(cycle.unreachable(network));
+    }
+
+    // once they're all looping, combine the residues of each ghost one at a time. A combined
+    // modulus past `u64::MAX` leaves at most one step a `u64` can reach, its residue, which is
+    // checked against the remaining ghosts directly
+    let mut residues = vec![(0_u64, 1_u64)];
+    let mut candidates = Vec::new();
+    let mut too_far = false;
+    for (i, cycle) in cycles.iter().enumerate() {
+        let mut combined = Vec::new();
+        for &r in &residues {
+            for h in cycle.residues() {
+                let Some((a, n)) = crt(r, (h, cycle.length)) else {
+                    continue;
+                };
+                match (u64::try_from(a), u64::try_from(n)) {
+                    (Ok(a), Ok(n)) => combined.push((a, n)),
+                    (Ok(a), Err(_)) if a >= loops_from => candidates.push(a),
+                    _ => too_far = true,
+                }
+            }
+        }
+        // a candidate the other ghosts miss only comes round again past `u64::MAX`
+        candidates.retain(|&a| {
+            let hit = cycles[i + 1..].iter().all(|c| c.hits(a));
+            too_far |= !hit;
+            hit
+        });
+        if combined.len() > MAX_RESIDUES {
+            return check_longest(&cycles);
+        }
+        residues = combined;
+    }
+
+    // the smallest solution that isn't before every ghost has started looping
+    for (a, n) in residues {
+        let step = match loops_from.checked_sub(a) {
+            Some(behind) => behind
+                .div_ceil(n)
+                .checked_mul(n)
+                .and_then(|ahead| ahead.checked_add(a)),
+            None => Some(a),
+        };
+        match step {
+            Some(step) => candidates.push(step),
+            None => too_far = true,
+        }
+    }
+    match candidates.into_iter().min() {
+        Some(step) => Ok(step),
+        None if too_far => Err(TraversalError::TooFar),
+        None => Err(TraversalError::Apart),
+    }
+}
+
+// least common multiple, if it fits
+fn lcm(a: u64, b: u64) -> Option<u64> {
+    let g = extended_gcd(a as i128, b as i128).0 as u64;
+    (a / g).checked_mul(b)
+}
+
+// go through the goals of the ghost with the longest cycle in order, checking each one against the
+// other ghosts, until the cycles have all lined up again
+fn check_longest(cycles: &[Cycle]) -> Result<u64, TraversalError> {
+    let longest = cycles.iter().max_by_key(|c| c.length).unwrap();
+    let loops_from = cycles.iter().map(|c| c.start).max().unwrap();
+    let offsets: Vec<_> = longest
+        .hits
+        .iter()
+        .filter(|&&h| h >= longest.start)
+        .map(|h| h - longest.start)
+        .collect();
+
+    // after the lcm of the cycles every ghost is back where it was, without an lcm that fits
+    // the search stops where the steps stop fitting
+    let limit = cycles
+        .iter()
+        .try_fold(1, |acc, c| lcm(acc, c.length))
+        .and_then(|l| loops_from.checked_add(l));
+    let mut lap = longest.start + (loops_from - longest.start) / longest.length * longest.length;
+    loop {
+        for &offset in &offsets {
+            let Some(step) = lap.checked_add(offset) else {
+                return Err(TraversalError::TooFar);
+            };
+            if limit.is_some_and(|limit| step >= limit) {
+                return Err(TraversalError::Apart);
+            }
+            if step >= loops_from && cycles.iter().all(|c| c.hits(step)) {
+                return Ok(step);
+            }
+        }
+        lap = lap
+            .checked_add(longest.length)
+            .ok_or(TraversalError::TooFar)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn goals(network: &Network) -> Vec<bool> {
+        network
+            .nodes()
+            .map(|n| network.name(n).ends_with('Z'))
+            .collect()
+    }
+
+    fn starts(network: &Network) -> Vec<u32> {
+        network
+            .nodes()
+            .filter(|&n| network.name(n).ends_with('A'))
+            .collect()
+    }
+
+    #[test]
+    fn check_crt() {
+        assert_eq!(crt((2, 3), (3, 4)), Some((11, 12)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((0, 4), (1, 6)), None);
+        assert_eq!(crt((0, 1), (5, 7)), Some((5, 7)));
+        // the product of moduli this big only fits a u128
+        let big = u64::MAX - 58;
+        assert_eq!(
+            crt((1, big), (0, 2)),
+            Some((big as u128 + 1, big as u128 * 2))
+        );
+    }
+
+    #[test]
+    fn check_offsets() {
+        // 1A reaches a goal after 2 steps and every 3 after that, 2A after 3 steps and every 4
+        // after that, so taking the LCM of the first hits (6) would be wrong
+        let network = Network::parse(indoc! {"
+            L
+
+            11A = (11B, 11B)
+            11B = (11Z, 11Z)
+            11Z = (11C, 11C)
+            11C = (11D, 11D)
+            11D = (11Z, 11Z)
+            22A = (22B, 22B)
+            22B = (22C, 22C)
+            22C = (22Z, 22Z)
+            22Z = (22D, 22D)
+            22D = (22E, 22E)
+            22E = (22F, 22F)
+            22F = (22Z, 22Z)"
+        })
+        .unwrap();
+        let is_goal = goals(&network);
+        let a = network.node("11A").unwrap();
+        assert_eq!(
+            Cycle::analyze(&network, a, &is_goal),
+            Cycle {
+                start: 2,
+                length: 3,
+                hits: vec![2],
+                path: vec![0, 1, 2, 3, 4],
+            }
+        );
+        assert_eq!(first_meeting(&network, &starts(&network), &is_goal), Ok(11));
+    }
+
+    #[test]
+    fn check_several_goals() {
+        // 11A passes goals 1 and 3 steps into a cycle of 4, 22A is on its goal 2 steps into a
+        // cycle of 5, so they only meet when 11A is on its second goal
+        let network = Network::parse(indoc! {"
+            L
+
+            11A = (1PZ, 1PZ)
+            1PZ = (11B, 11B)
+            11B = (1QZ, 1QZ)
+            1QZ = (11C, 11C)
+            11C = (1PZ, 1PZ)
+            22A = (22B, 22B)
+            22B = (22Z, 22Z)
+            22Z = (22C, 22C)
+            22C = (22D, 22D)
+            22D = (22A, 22A)"
+        })
+        .unwrap();
+        let is_goal = goals(&network);
+        let a = network.node("11A").unwrap();
+        assert_eq!(Cycle::analyze(&network, a, &is_goal).residues(), vec![1, 3]);
+        assert_eq!(first_meeting(&network, &starts(&network), &is_goal), Ok(7));
+
+        // the two ghosts can never be on a goal together
+        let network = Network::parse(indoc! {"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11A, 11A)
+            22A = (22B, 22B)
+            22B = (22Z, 22Z)
+            22Z = (22B, 22B)"
+        })
+        .unwrap();
+        let is_goal = goals(&network);
+        assert_eq!(
+            first_meeting(&network, &starts(&network), &is_goal),
+            Err(TraversalError::Apart)
+        );
+    }
+
+    #[test]
+    fn check_unreachable() {
+        // 22A passes its goal on the way in, but then loops between 22B and 22C forever
+        let network = Network::parse(indoc! {"
+            LR
+
+            11A = (11B, 11B)
+            11B = (11Z, 11Z)
+            11Z = (11B, 11B)
+            22A = (22Z, 22Z)
+            22Z = (22B, 22B)
+            22B = (22C, 22B)
+            22C = (22C, 22B)"
+        })
+        .unwrap();
+        let is_goal = goals(&network);
+        let error = first_meeting(&network, &starts(&network), &is_goal).unwrap_err();
+        assert_eq!(
+            error,
+            TraversalError::Unreachable {
+                start: "22A".to_string(),
+                cycle: vec!["22B".to_string(), "22C".to_string()],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "no goal is reachable from 22A, it loops through 22B -> 22C"
+        );
+    }
+
+    #[test]
+    fn check_against_simulation() {
+        // small random networks, checked against walking the ghosts step by step
+        let mut seed: u64 = 3;
+        let mut random = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        for _ in 0..300 {
+            let size = random(8) + 2;
+            let directions: String = (0..random(3) + 1)
+                .map(|_| if random(2) == 0 { 'L' } else { 'R' })
+                .collect();
+            let name = |n: u64| match n {
+                0 => "AAA".to_string(),
+                1 => "BBA".to_string(),
+                n if n % 3 == 0 => format!("N{n}Z"),
+                n => format!("N{n}X"),
+            };
+            let mut input = format!("{directions}\n\n");
+            for n in 0..size {
+                let (left, right) = (random(size), random(size));
+                input.push_str(&format!(
+                    "{} = ({}, {})\n",
+                    name(n),
+                    name(left),
+                    name(right)
+                ));
+            }
+
+            let network = Network::parse(&input).unwrap();
+            let is_goal = goals(&network);
+            let starts = starts(&network);
+            let cycles: Vec<_> = starts
+                .iter()
+                .map(|&s| Cycle::analyze(&network, s, &is_goal))
+                .collect();
+            let expected = walk(&network, &starts, &is_goal, &cycles);
+            assert_eq!(
+                first_meeting(&network, &starts, &is_goal).ok(),
+                expected,
+                "{input}"
+            );
+            // the fallback only looks for meetings once every ghost is looping with a goal in it
+            let looping = expected.is_none_or(|step| step >= loops_from(&cycles));
+            if looping && cycles.iter().all(|c| !c.residues().is_empty()) {
+                assert_eq!(check_longest(&cycles).ok(), expected, "{input}");
+            }
+        }
+    }
+
+    fn loops_from(cycles: &[Cycle]) -> u64 {
+        cycles.iter().map(|c| c.start).max().unwrap()
+    }
+
+    // walk every ghost together until they're all on a goal, which has to happen before the
+    // cycles all line up again
+    fn walk(network: &Network, starts: &[u32], is_goal: &[bool], cycles: &[Cycle]) -> Option<u64> {
+        let limit = cycles
+            .iter()
+            .try_fold(1, |acc, c| lcm(acc, c.length))
+            .unwrap()
+            + loops_from(cycles);
+        let mut current = starts.to_vec();
+        for (step, &direction) in network.directions.iter().cycle().enumerate() {
+            if current.iter().all(|&n| is_goal[n as usize]) {
+                return Some(step as u64);
+            }
+            if step as u64 >= limit {
+                break;
+            }
+            for node in current.iter_mut() {
+                *node = network.step(*node, direction);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn check_too_far() {
+        // twenty ghosts on cycles of different primes, each on its goal one step before it gets
+        // back to its start, so they only meet one step before the product of the primes
+        let primes = (101_u64..200)
+            .filter(|&n| (2..n).all(|d| n % d != 0))
+            .take(20);
+        let mut input = "L\n\n".to_string();
+        for (ghost, prime) in primes.enumerate() {
+            for i in 0..prime {
+                let name = |i: u64| match i {
+                    0 => format!("G{ghost}A"),
+                    i if i == prime - 1 => format!("G{ghost}Z"),
+                    i => format!("G{ghost}N{i}"),
+                };
+                let next = name((i + 1) % prime);
+                input.push_str(&format!("{} = ({next}, {next})\n", name(i)));
+            }
+        }
+        let network = Network::parse(&input).unwrap();
+        let is_goal = goals(&network);
+        let starts = starts(&network);
+        assert_eq!(starts.len(), 20);
+        assert_eq!(
+            first_meeting(&network, &starts, &is_goal),
+            Err(TraversalError::TooFar)
+        );
+        let cycles: Vec<_> = starts
+            .iter()
+            .map(|&s| Cycle::analyze(&network, s, &is_goal))
+            .collect();
+        assert_eq!(check_longest(&cycles[..2]), Ok(101 * 103 - 1));
+
+        // a combined modulus past u64::MAX still leaves the one step a u64 can reach
+        assert_eq!(
+            crt((5, u64::MAX), (5, u64::MAX - 1)),
+            Some((5, u64::MAX as u128 * (u64::MAX - 1) as u128))
+        );
+    }
+}
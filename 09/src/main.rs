@@ -1,47 +1,65 @@
+use num_bigint::BigInt;
+use sequence::Sequence;
+use std::env;
+
+mod rational;
+mod sequence;
+mod stream;
+
 fn main() {
     let input = include_str!("input.txt");
+    let args: Vec<_> = env::args().skip(1).collect();
 
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
-}
-
-// Given a vector of numbers, returns the next number in the current line
-fn next_number(numbers: Vec<i32>, f: fn(Vec<i32>, i32) -> i32) -> i32 {
-    // If the line is all zeros, we've hit the bottom and should return.
-    if numbers.iter().all(|e| *e == 0) {
-        return 0;
+    let (part1, part2) = solve(input, args.iter().any(|arg| arg == "--strict"));
+    println!("Part 1: {part1}");
+    println!("Part 2: {part2}");
+    if args.iter().any(|arg| arg == "--explain") {
+        print!("{}", explain(input));
     }
+}
 
-    // Get the next number from the line after ours so we can calculate our next number
-    let next_child = next_number(numbers.windows(2).map(|e| e[1] - e[0]).collect(), f);
-
-    return f(numbers, next_child);
+// the histories are summed as they're read, without keeping any of them around, in one pass for
+// both parts. A history that never reaches a row of zeros is extrapolated through every value it
+// has, which is only a guess, so warn about it or with `--strict` refuse to guess at all
+fn solve(input: &str, strict: bool) -> (BigInt, BigInt) {
+    let totals = stream::totals(input.as_bytes(), strict, |guess| {
+        eprintln!("warning: {guess}")
+    })
+    .unwrap_or_else(|e| panic!("{e}"));
+    (totals.next, totals.previous)
 }
 
-fn part1(input: &str) -> i32 {
-    let mut sum = 0;
-    for line in input.lines() {
-        sum += next_number(
-            line.split_whitespace()
-                .map(|e| e.parse::<i32>().unwrap())
-                .collect(),
-            |a, b| a[a.len() - 1] + b,
-        )
+// the polynomial behind each history, lowest power first
+fn explain(input: &str) -> String {
+    let mut explained = String::new();
+    for (line, sequence) in parse_input(input).iter().enumerate() {
+        let coefficients: Vec<_> = sequence
+            .coefficients()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        explained.push_str(&format!(
+            "history {}: degree {}, coefficients [{}]\n",
+            line + 1,
+            sequence.degree(),
+            coefficients.join(", ")
+        ));
+        if let Some(residual) = sequence.residual() {
+            let residual: Vec<_> = residual.iter().map(|v| v.to_string()).collect();
+            explained.push_str(&format!(
+                "  not a polynomial, residual row {}\n",
+                residual.join(" ")
+            ));
+        }
     }
-    sum
+    explained
 }
 
-fn part2(input: &str) -> i32 {
-    let mut sum = 0;
-    for line in input.lines() {
-        sum += next_number(
-            line.split_whitespace()
-                .map(|e| e.parse::<i32>().unwrap())
-                .collect(),
-            |a, b| a[0] - b,
-        );
-    }
-    sum
+fn parse_input(input: &str) -> Vec<Sequence> {
+    input
+        .lines()
+        .map(|line| Sequence::parse(line).unwrap_or_else(|e| panic!("{e}")))
+        .collect()
 }
 
 #[cfg(test)]
@@ -56,13 +74,37 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let result = part1(INPUT);
-        assert_eq!(result, 114)
+        let (result, _) = solve(INPUT, false);
+        assert_eq!(result, BigInt::from(114))
     }
 
     #[test]
     fn check_part2() {
-        let result = part2(INPUT);
-        assert_eq!(result, 2)
+        let (_, result) = solve(INPUT, false);
+        assert_eq!(result, BigInt::from(2))
+    }
+
+    #[test]
+    fn check_strict() {
+        // every history in the example reaches zeros
+        assert_eq!(solve(INPUT, true), (BigInt::from(114), BigInt::from(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "history 2: the differences never reach zeros, the last row is 1 2")]
+    fn check_strict_guess() {
+        solve("1 2 3\n1 2 4 8", true);
+    }
+
+    #[test]
+    fn check_explain() {
+        assert_eq!(
+            explain(INPUT),
+            indoc! {"
+                history 1: degree 1, coefficients [0, 3]
+                history 2: degree 2, coefficients [1, 3/2, 1/2]
+                history 3: degree 3, coefficients [10, 11/3, -1, 1/3]
+            "}
+        );
     }
 }
@@ -0,0 +1,93 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use std::fmt;
+use std::ops::Add;
+
+/// An exact fraction.
+///
+/// Always stored in lowest terms with a positive denominator, so equal values compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+
+impl Rational {
+    pub fn new(numer: BigInt, denom: BigInt) -> Rational {
+        assert!(!denom.is_zero(), "zero denominator");
+        let gcd = numer.gcd(&denom);
+        let sign = if denom.is_negative() { -1 } else { 1 };
+        Rational {
+            numer: numer / &gcd * sign,
+            denom: denom / gcd * sign,
+        }
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom.is_one()
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(numer: BigInt) -> Rational {
+        Rational {
+            numer,
+            denom: BigInt::one(),
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(numer: i64) -> Rational {
+        Rational::from(BigInt::from(numer))
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numer * &other.denom + &other.numer * &self.denom,
+            &self.denom * &other.denom,
+        )
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(numer: i64, denom: i64) -> Rational {
+        Rational::new(BigInt::from(numer), BigInt::from(denom))
+    }
+
+    #[test]
+    fn check_rational() {
+        assert_eq!(ratio(4, -6), ratio(-2, 3));
+        assert_eq!(ratio(0, -5), Rational::from(0));
+        assert_eq!(&ratio(1, 6) + &ratio(1, 3), ratio(1, 2));
+        assert_eq!(&ratio(1, 2) + &ratio(1, 2), Rational::from(1));
+        assert_eq!(ratio(-3, 6).to_string(), "-1/2");
+        assert_eq!(ratio(8, 4).to_string(), "2");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::rational::Rational;
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum SequenceError {
+    Empty,
+    Value(String),
+    Read(String),
+    NotPolynomial { residual: Vec<BigInt> },
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SequenceError::Empty => write!(f, "the history has no values"),
+            SequenceError::Value(value) => write!(f, "{value} is not an integer"),
+            SequenceError::Read(e) => write!(f, "could not read the histories: {e}"),
+            SequenceError::NotPolynomial { residual } => {
+                let residual: Vec<_> = residual.iter().map(|v| v.to_string()).collect();
+                write!(
+                    f,
+                    "the differences never reach zeros, the last row is {}",
+                    residual.join(" ")
+                )
+            }
+        }
+    }
+}
+
+/// A history of values, and the polynomial through them.
+///
+/// The first value of each row of the difference table is kept, which is the polynomial in
+/// Newton's forward-difference form: `f(x) = Σ Δᵏf(0) * C(x, k)`, where `x = 0` is the first
+/// value of the history. Everything is done with big integers so nothing can overflow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    // Δᵏf(0) for every row of the table
+    differences: Vec<BigInt>,
+}
+
+impl Sequence {
+    pub fn new(values: &[BigInt]) -> Result<Sequence, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+
+        // work down the table, keeping the first value of each row
+        let mut row = values.to_vec();
+        let mut differences = Vec::with_capacity(values.len());
+        while let Some(first) = row.first() {
+            differences.push(first.clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+        Ok(Sequence { differences })
+    }
+
+    pub fn parse(line: &str) -> Result<Sequence, SequenceError> {
+        let values = line
+            .split_whitespace()
+            .map(|v| v.parse().map_err(|_| SequenceError::Value(v.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Sequence::new(&values)
+    }
+
+    /// How many values are in the history.
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    /// Whether the difference table reaches a row of zeros, so the history really is a polynomial
+    /// and not just the values the interpolation happens to go through.
+    pub fn is_polynomial(&self) -> bool {
+        self.differences.last().unwrap().is_zero()
+    }
+
+    /// The last row of the difference table with more than one value, when it doesn't lead to
+    /// a row of zeros.
+    pub fn residual(&self) -> Option<Vec<BigInt>> {
+        if self.is_polynomial() {
+            return None;
+        }
+        // the row above the single value at the bottom is its first value, and that plus the bottom
+        match self.differences.as_slice() {
+            [.., above, bottom] => Some(vec![above.clone(), above + bottom]),
+            _ => Some(self.differences.clone()),
+        }
+    }
+
+    /// Refuse to guess: fail with the residual row if the history isn't a polynomial.
+    pub fn strict(self) -> Result<Sequence, SequenceError> {
+        match self.residual() {
+            Some(residual) => Err(SequenceError::NotPolynomial { residual }),
+            None => Ok(self),
+        }
+    }
+
+    /// The degree of the polynomial, the zero polynomial counts as degree 0.
+    pub fn degree(&self) -> usize {
+        self.differences
+            .iter()
+            .rposition(|d| !d.is_zero())
+            .unwrap_or(0)
+    }
+
+    /// The value at position `x`, where the history is at `0..len`.
+    pub fn value(&self, x: i64) -> BigInt {
+        // C(x, k) = C(x, k - 1) * (x - k + 1) / k, which always divides exactly
+        let x = BigInt::from(x);
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+        for (k, difference) in self.differences.iter().enumerate().take(self.degree() + 1) {
+            if k > 0 {
+                binomial = binomial * (&x - (k - 1)) / k;
+            }
+            value += difference * &binomial;
+        }
+        value
+    }
+
+    /// The value `steps` after the end of the history.
+    pub fn forward(&self, steps: i64) -> BigInt {
+        self.value(self.len() as i64 - 1 + steps)
+    }
+
+    /// The value `steps` before the start of the history.
+    pub fn backward(&self, steps: i64) -> BigInt {
+        self.value(-steps)
+    }
+
+    /// The polynomial's coefficients, constant term first, with `x = 0` at the start of the history.
+    pub fn coefficients(&self) -> Vec<Rational> {
+        let degree = self.degree();
+        let mut coefficients = vec![Rational::from(0); degree + 1];
+
+        // expand Δᵏf(0) / k! * x(x - 1)...(x - k + 1) term by term
+        let mut falling = vec![BigInt::one()];
+        let mut factorial = BigInt::one();
+        for (k, difference) in self.differences.iter().enumerate().take(degree + 1) {
+            if k > 0 {
+                // multiply the falling factorial by (x - (k - 1))
+                let shift = BigInt::from(k - 1);
+                let mut next = vec![BigInt::zero(); falling.len() + 1];
+                for (i, c) in falling.iter().enumerate() {
+                    next[i + 1] += c;
+                    next[i] -= c * &shift;
+                }
+                falling = next;
+                factorial *= k;
+            }
+            for (coefficient, c) in coefficients.iter_mut().zip(&falling) {
+                *coefficient = &*coefficient + &Rational::new(difference * c, factorial.clone());
+            }
+        }
+        coefficients
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(values: &[i64]) -> Vec<BigInt> {
+        values.iter().map(|&v| BigInt::from(v)).collect()
+    }
+
+    fn ratio(numer: i64, denom: i64) -> Rational {
+        Rational::new(BigInt::from(numer), BigInt::from(denom))
+    }
+
+    #[test]
+    fn check_extrapolate() {
+        let sequence = Sequence::parse("10 13 16 21 30 45").unwrap();
+        assert_eq!(sequence.degree(), 3);
+        assert_eq!(sequence.forward(1), BigInt::from(68));
+        assert_eq!(sequence.backward(1), BigInt::from(5));
+        assert_eq!(sequence.value(2), BigInt::from(16));
+
+        // several steps either way is the same as extending the history one step at a time
+        let mut values = big(&[10, 13, 16, 21, 30, 45]);
+        for _ in 0..5 {
+            let next = Sequence::new(&values).unwrap().forward(1);
+            values.push(next);
+        }
+        assert_eq!(sequence.forward(5), values[10]);
+        let mut values = big(&[10, 13, 16, 21, 30, 45]);
+        for _ in 0..5 {
+            let previous = Sequence::new(&values).unwrap().backward(1);
+            values.insert(0, previous);
+        }
+        assert_eq!(sequence.backward(5), values[0]);
+    }
+
+    #[test]
+    fn check_coefficients() {
+        // x² / 2 + x / 2 + 1
+        let sequence = Sequence::parse("1 2 4 7 11").unwrap();
+        assert_eq!(sequence.degree(), 2);
+        assert_eq!(
+            sequence.coefficients(),
+            vec![ratio(1, 1), ratio(1, 2), ratio(1, 2)]
+        );
+
+        // sums of squares, x³ / 3 + x² / 2 + x / 6
+        let sequence = Sequence::parse("0 1 5 14 30 55").unwrap();
+        assert_eq!(sequence.degree(), 3);
+        assert_eq!(
+            sequence.coefficients(),
+            vec![ratio(0, 1), ratio(1, 6), ratio(1, 2), ratio(1, 3)]
+        );
+
+        let constant = Sequence::parse("0 0 0").unwrap();
+        assert_eq!(constant.degree(), 0);
+        assert_eq!(constant.coefficients(), vec![ratio(0, 1)]);
+    }
+
+    #[test]
+    fn check_no_overflow() {
+        // the cubes go far past what an i64 can hold
+        let cubes: Vec<_> = (0..4_i64).map(|x| BigInt::from(x * x * x)).collect();
+        let sequence = Sequence::new(&cubes).unwrap();
+        assert_eq!(
+            sequence.forward(10_000_000),
+            BigInt::from(10_000_003_i64).pow(3)
+        );
+        assert_eq!(
+            sequence.backward(3_000_000),
+            -BigInt::from(3_000_000_i64).pow(3)
+        );
+    }
+
+    #[test]
+    fn check_not_polynomial() {
+        // rows are 1 2 4 8, 1 2 4, 1 2 and 1, which never reach zeros
+        let sequence = Sequence::parse("1 2 4 8").unwrap();
+        assert!(!sequence.is_polynomial());
+        assert_eq!(sequence.residual(), Some(big(&[1, 2])));
+        let error = sequence.clone().strict().unwrap_err();
+        assert_eq!(
+            error,
+            SequenceError::NotPolynomial {
+                residual: big(&[1, 2])
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the differences never reach zeros, the last row is 1 2"
+        );
+        // without strict mode the guess goes through every value
+        assert_eq!(sequence.forward(1), BigInt::from(15));
+
+        assert_eq!(Sequence::parse("7").unwrap().residual(), Some(big(&[7])));
+        assert!(Sequence::parse("0").unwrap().is_polynomial());
+        // one zero at the bottom is enough
+        let sequence = Sequence::parse("1 4 9 16").unwrap();
+        assert_eq!(sequence.residual(), None);
+        assert_eq!(sequence.clone().strict(), Ok(sequence));
+    }
+
+    #[test]
+    fn check_errors() {
+        assert_eq!(Sequence::parse(""), Err(SequenceError::Empty));
+        assert_eq!(
+            Sequence::parse("1 2 x"),
+            Err(SequenceError::Value("x".to_string()))
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::sequence::{Sequence, SequenceError};
+use num_bigint::BigInt;
+use std::fmt;
+use std::io::BufRead;
+
+/// The values either side of a history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ends {
+    pub previous: i64,
+    pub next: i64,
+}
+
+/// Extrapolates histories one after another without allocating for each of them.
+///
+/// The difference table is worked out in place in one buffer that's reused for every history,
+/// so once it has grown to fit the longest history nothing else is allocated.
+#[derive(Debug, Default)]
+pub struct Extrapolator {
+    values: Vec<i64>,
+}
+
+impl Extrapolator {
+    pub fn new() -> Extrapolator {
+        Extrapolator::default()
+    }
+
+    /// The values either side of the history on `line`, or `None` if working them out
+    /// overflows an `i64`.
+    pub fn extrapolate(&mut self, line: &str, strict: bool) -> Result<Option<Ends>, SequenceError> {
+        self.values.clear();
+        for value in line.split_whitespace() {
+            let value = value
+                .parse()
+                .map_err(|_| SequenceError::Value(value.to_string()))?;
+            self.values.push(value);
+        }
+        if self.values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+
+        // each pass turns values[..len] into the next row of the table in values[..len - 1],
+        // the next value is the sum of the last value of each row and the previous value is the
+        // sum of the first values with alternating signs
+        let (mut previous, mut next) = (0_i64, 0_i64);
+        let mut len = self.values.len();
+        let mut negate = false;
+        loop {
+            let row = &mut self.values[..len];
+            if row.iter().all(|&v| v == 0) {
+                break;
+            }
+            let first = if negate {
+                previous.checked_sub(row[0])
+            } else {
+                previous.checked_add(row[0])
+            };
+            let (Some(first), Some(last)) = (first, next.checked_add(row[len - 1])) else {
+                return Ok(None);
+            };
+            (previous, next, negate) = (first, last, !negate);
+
+            if len == 1 {
+                // the table ran out before reaching zeros
+                if strict {
+                    // the previous pass left the last value of the row above the bottom in
+                    // values[1], and the bottom is its only difference
+                    let residual = match self.values.get(1) {
+                        Some(&last) => vec![last - self.values[0], last],
+                        None => vec![self.values[0]],
+                    };
+                    return Err(SequenceError::NotPolynomial {
+                        residual: residual.into_iter().map(BigInt::from).collect(),
+                    });
+                }
+                break;
+            }
+            for i in 0..len - 1 {
+                let Some(difference) = row[i + 1].checked_sub(row[i]) else {
+                    return Ok(None);
+                };
+                row[i] = difference;
+            }
+            len -= 1;
+        }
+        Ok(Some(Ends { previous, next }))
+    }
+}
+
+/// Something wrong with one of the histories, numbered from 1.
+#[derive(Debug, PartialEq)]
+pub struct HistoryError {
+    pub history: usize,
+    pub error: SequenceError,
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "history {}: {}", self.history, self.error)
+    }
+}
+
+/// The sums of the values either side of every history.
+#[derive(Debug, Default, PartialEq)]
+pub struct Totals {
+    pub previous: BigInt,
+    pub next: BigInt,
+}
+
+/// Sum the previous and next values of every history read from `reader`.
+///
+/// Each line is read into the same buffer, and a history that overflows an `i64` falls back to
+/// an exact `Sequence`, so memory use only depends on the longest line. A history that isn't a
+/// polynomial is an error with `strict`, otherwise it's a guess through every value and is passed
+/// to `guess` as soon as it's found.
+pub fn totals(
+    mut reader: impl BufRead,
+    strict: bool,
+    mut guess: impl FnMut(HistoryError),
+) -> Result<Totals, HistoryError> {
+    let mut extrapolator = Extrapolator::new();
+    let mut totals = Totals::default();
+    let mut line = String::new();
+    for history in 1.. {
+        let at = |error| HistoryError { history, error };
+        let read = reader.read_line(&mut line);
+        if read.map_err(|e| at(SequenceError::Read(e.to_string())))? == 0 {
+            break;
+        }
+
+        // check strictly either way, and only go back for the guess when there's a residual
+        let ends = match extrapolator.extrapolate(&line, true) {
+            Err(error @ SequenceError::NotPolynomial { .. }) if !strict => {
+                guess(at(error));
+                extrapolator.extrapolate(&line, false)
+            }
+            ends => ends,
+        };
+        if let Some(ends) = ends.map_err(at)? {
+            totals.previous += ends.previous;
+            totals.next += ends.next;
+        } else {
+            let sequence = match Sequence::parse(&line).and_then(Sequence::strict) {
+                Err(error @ SequenceError::NotPolynomial { .. }) if !strict => {
+                    guess(at(error));
+                    Sequence::parse(&line)
+                }
+                sequence => sequence,
+            };
+            let sequence = sequence.map_err(at)?;
+            totals.previous += sequence.backward(1);
+            totals.next += sequence.forward(1);
+        }
+        line.clear();
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_extrapolate() {
+        let mut extrapolator = Extrapolator::new();
+        for line in [
+            "0 3 6 9 12 15",
+            "1 3 6 10 15 21",
+            "10 13 16 21 30 45",
+            "1 2 4 8",
+            "5",
+        ] {
+            let sequence = Sequence::parse(line).unwrap();
+            let ends = extrapolator.extrapolate(line, false).unwrap().unwrap();
+            assert_eq!(BigInt::from(ends.previous), sequence.backward(1), "{line}");
+            assert_eq!(BigInt::from(ends.next), sequence.forward(1), "{line}");
+            assert_eq!(
+                extrapolator.extrapolate(line, true).map(|_| ()),
+                sequence.strict().map(|_| ()),
+                "{line}"
+            );
+        }
+        assert_eq!(
+            extrapolator.extrapolate("  ", false),
+            Err(SequenceError::Empty)
+        );
+    }
+
+    #[test]
+    fn check_overflow() {
+        // the differences of these overflow an i64, so the exact sequence takes over
+        let line = format!("{} {} {}\n", i64::MIN, i64::MAX, i64::MIN);
+        assert_eq!(Extrapolator::new().extrapolate(&line, false), Ok(None));
+        let sequence = Sequence::parse(&line).unwrap();
+        let mut guesses = 0;
+        let totals = totals(line.as_bytes(), false, |_| guesses += 1).unwrap();
+        assert_eq!(
+            (totals.previous, totals.next),
+            (sequence.backward(1), sequence.forward(1))
+        );
+        // three values whose differences don't reach zeros are only a guess
+        assert_eq!(guesses, 1);
+    }
+
+    #[test]
+    fn check_stream() {
+        // a long stream of histories only ever needs a buffer as long as its longest line
+        let histories = (0..20_000_i64).map(|i| {
+            let values: Vec<_> = (0..21).map(|x| (i % 7 - 3) * x * x + i * x - i).collect();
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+                + "\n"
+        });
+        let input: String = histories.collect();
+
+        let mut extrapolator = Extrapolator::new();
+        extrapolator
+            .extrapolate(input.lines().next().unwrap(), true)
+            .unwrap();
+        let capacity = extrapolator.values.capacity();
+        let mut expected = (BigInt::default(), BigInt::default());
+        for line in input.lines() {
+            let ends = extrapolator.extrapolate(line, true).unwrap().unwrap();
+            expected.0 += ends.previous;
+            expected.1 += ends.next;
+        }
+        assert_eq!(extrapolator.values.capacity(), capacity);
+
+        let totals = totals(input.as_bytes(), true, |_| ()).unwrap();
+        assert_eq!((totals.previous, totals.next), expected);
+    }
+
+    #[test]
+    fn check_guesses() {
+        // the second and fourth histories never reach zeros, the fourth one overflows an i64
+        let input = format!(
+            "1 3 5\n1 2 4 8\n0\n{} {} {}\n",
+            i64::MIN,
+            i64::MAX,
+            i64::MIN
+        );
+        let mut guesses = Vec::new();
+        let guessed = totals(input.as_bytes(), false, |g| guesses.push(g)).unwrap();
+        let histories: Vec<_> = guesses.iter().map(|g| g.history).collect();
+        assert_eq!(histories, [2, 4]);
+        assert_eq!(
+            guesses[0].to_string(),
+            "history 2: the differences never reach zeros, the last row is 1 2"
+        );
+        assert_eq!(
+            guessed.next,
+            BigInt::from(7 + 15)
+                + Sequence::parse(input.lines().last().unwrap())
+                    .unwrap()
+                    .forward(1)
+        );
+
+        assert_eq!(
+            totals(input.as_bytes(), true, |_| ()).unwrap_err().history,
+            2
+        );
+        assert_eq!(
+            totals("1 2\n3 x\n".as_bytes(), false, |_| ()),
+            Err(HistoryError {
+                history: 2,
+                error: SequenceError::Value("x".to_string())
+            })
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{Coord, Pipe, Sketch, EAST, NORTH, SOUTH, WEST};
+
+/// What a tile of the sketch is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    /// On the loop through the start.
+    MainLoop,
+    /// On some other closed loop.
+    OtherLoop,
+    /// On a chain of pipes that doesn't close into a loop.
+    Dangling,
+    Ground,
+}
+
+impl Tile {
+    pub fn symbol(self) -> char {
+        match self {
+            Tile::MainLoop => 'M',
+            Tile::OtherLoop => 'O',
+            Tile::Dangling => 'D',
+            Tile::Ground => '.',
+        }
+    }
+}
+
+/// Pipes that are joined up to each other.
+#[derive(Debug, PartialEq)]
+pub struct Component {
+    /// Every tile has both of its ends joined to another tile in the component.
+    pub closed: bool,
+    /// The component goes through the start.
+    pub main: bool,
+    pub tiles: Vec<Coord>,
+    /// The bottom left and top right corners of the bounding box, inclusive.
+    pub min: Coord,
+    pub max: Coord,
+}
+
+impl Component {
+    pub fn length(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+/// Every group of joined up pipes in the sketch, and what each tile is part of.
+#[derive(Debug)]
+pub struct Analysis {
+    pub components: Vec<Component>,
+    pub tiles: Vec<Vec<Tile>>,
+}
+
+// the pipe on a tile, with the start replaced by its real shape once the loop has been traced
+fn pipe_at(sketch: &Sketch<Pipe>, coord: Coord) -> Pipe {
+    match sketch.get(coord) {
+        Pipe::Start if sketch.pipe.len() > 1 => sketch.start_pipe(),
+        pipe => pipe,
+    }
+}
+
+// the neighbours a tile's pipe is joined to, both pipes have to point at each other
+fn joined(sketch: &Sketch<Pipe>, coord: Coord) -> Vec<Coord> {
+    [NORTH, SOUTH, EAST, WEST]
+        .into_iter()
+        .filter(|&direction| pipe_at(sketch, coord).connects(direction))
+        .map(|direction| (direction, coord + direction))
+        .filter(|&(direction, next)| {
+            sketch.contains(next) && pipe_at(sketch, next).connects(Coord::default() - direction)
+        })
+        .map(|(_, next)| next)
+        .collect()
+}
+
+/// Find every loop and dead-end chain of pipes, in the order their first tile is scanned.
+pub fn analyze(sketch: &Sketch<Pipe>) -> Analysis {
+    let on_loop = sketch.on_loop();
+    let mut tiles: Vec<_> = sketch
+        .sketch
+        .iter()
+        .map(|row| vec![Tile::Ground; row.len()])
+        .collect();
+    let mut seen: Vec<_> = sketch
+        .sketch
+        .iter()
+        .map(|row| vec![false; row.len()])
+        .collect();
+    let mut components = Vec::new();
+
+    for (y, row) in sketch.sketch.iter().enumerate() {
+        for x in 0..row.len() {
+            let coord = Coord {
+                x: x as isize,
+                y: y as isize,
+            };
+            if seen[y][x] || pipe_at(sketch, coord).ends().is_none() {
+                continue;
+            }
+
+            // flood out along the joins, a component is closed if every tile has two of them
+            seen[y][x] = true;
+            let mut stack = vec![coord];
+            let mut component = Vec::new();
+            let mut closed = true;
+            while let Some(current) = stack.pop() {
+                component.push(current);
+                let neighbours = joined(sketch, current);
+                closed &= neighbours.len() == 2;
+                for next in neighbours {
+                    if !seen[next.y as usize][next.x as usize] {
+                        seen[next.y as usize][next.x as usize] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort_by_key(|c| (c.y, c.x));
+
+            let main = sketch.pipe.len() > 1
+                && component
+                    .iter()
+                    .any(|c| on_loop[c.y as usize][c.x as usize]);
+            let tile = match (main, closed) {
+                (true, _) => Tile::MainLoop,
+                (false, true) => Tile::OtherLoop,
+                (false, false) => Tile::Dangling,
+            };
+            for c in &component {
+                tiles[c.y as usize][c.x as usize] = tile;
+            }
+            components.push(Component {
+                closed,
+                main,
+                min: Coord {
+                    x: component.iter().map(|c| c.x).min().unwrap(),
+                    y: component[0].y,
+                },
+                max: Coord {
+                    x: component.iter().map(|c| c.x).max().unwrap(),
+                    y: component[component.len() - 1].y,
+                },
+                tiles: component,
+            });
+        }
+    }
+    Analysis { components, tiles }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sketch;
+    use indoc::indoc;
+
+    #[test]
+    fn check_analyze() {
+        // a decoy loop on the right, and a chain along the bottom that goes nowhere
+        let mut sketch = parse_sketch(indoc! {"
+            .....F-7
+            S-7..|.|
+            |.|..L-J
+            L-J.F-.."
+        });
+        sketch.trace().unwrap();
+        let analysis = analyze(&sketch);
+
+        let summary: Vec<_> = analysis
+            .components
+            .iter()
+            .map(|c| {
+                (
+                    c.main,
+                    c.closed,
+                    c.length(),
+                    (c.min.x, c.min.y),
+                    (c.max.x, c.max.y),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (true, true, 8, (0, 0), (2, 2)),
+                (false, false, 2, (4, 0), (5, 0)),
+                (false, true, 8, (5, 1), (7, 3)),
+            ]
+        );
+
+        let tiles: Vec<String> = analysis
+            .tiles
+            .iter()
+            .rev()
+            .map(|row| row.iter().map(|t| t.symbol()).collect())
+            .collect();
+        assert_eq!(tiles, [".....OOO", "MMM..O.O", "M.M..OOO", "MMM.DD.."]);
+    }
+
+    #[test]
+    fn check_example() {
+        // the pipes that aren't on the example's loop are all left over bits of chains
+        let mut sketch = parse_sketch(indoc! {"
+            7-F7-
+            .FJ|7
+            SJLL7
+            |F--J
+            LJ.LJ"
+        });
+        sketch.trace().unwrap();
+        let analysis = analyze(&sketch);
+        assert_eq!(analysis.components.iter().filter(|c| c.main).count(), 1);
+        assert!(analysis.components.iter().all(|c| c.main || !c.closed));
+        let main = analysis.components.iter().find(|c| c.main).unwrap();
+        assert_eq!(main.length(), sketch.pipe.len() - 1);
+    }
+}
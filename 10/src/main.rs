@@ -1,10 +1,77 @@
+use render::Style;
+use std::env;
+use std::fmt;
 use std::ops::{Add, Sub};
+use std::process;
+
+mod components;
+mod render;
 
 fn main() {
     let input = include_str!("input.txt");
+    let args: Vec<_> = env::args().skip(1).collect();
+
+    // a sketch without a loop through the start has no answer, say where the loop breaks
+    match part1(input) {
+        Ok(part1) => println!("Part 1: {part1}"),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+    print_loop(input, &args);
+    if args.iter().any(|arg| arg == "--components") {
+        print!("{}", describe_components(input));
+    }
+}
+
+// every loop and dead-end chain, then a map of what each tile is part of
+fn describe_components(input: &str) -> String {
+    let sketch = traced(input).unwrap_or_else(|e| panic!("{e}"));
+    let analysis = components::analyze(&sketch);
+
+    let mut described = String::new();
+    for component in &analysis.components {
+        let kind = match (component.main, component.closed) {
+            (true, _) => "main loop",
+            (false, true) => "loop",
+            (false, false) => "chain",
+        };
+        described.push_str(&format!(
+            "{kind}: {} tiles from ({}, {}) to ({}, {})\n",
+            component.length(),
+            component.min.x,
+            component.min.y,
+            component.max.x,
+            component.max.y
+        ));
+    }
+    for row in analysis.tiles.iter().rev() {
+        described.extend(row.iter().map(|tile| tile.symbol()));
+        described.push('\n');
+    }
+    described
+}
 
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
+// `--render` draws the loop in the terminal and `--svg` as an SVG image, `--mark` shows which
+// tiles are inside it
+fn print_loop(input: &str, args: &[String]) {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    if !flag("--render") && !flag("--svg") {
+        return;
+    }
+
+    let sketch = traced(input).unwrap_or_else(|e| panic!("{e}"));
+    let style = Style {
+        dim: true,
+        mark: flag("--mark"),
+    };
+    if flag("--render") {
+        print!("{}", render::render(&sketch, style));
+    }
+    if flag("--svg") {
+        print!("{}", render::to_svg(&sketch, style));
+    }
 }
 
 const NORTH: Coord = Coord { x: 0, y: 1 };
@@ -20,7 +87,7 @@ struct Sketch<S> {
 }
 
 /// X, Y coordinate tuple
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 struct Coord {
     x: isize,
     y: isize,
@@ -47,6 +114,42 @@ impl Sub for Coord {
     }
 }
 
+#[derive(Debug, PartialEq)]
+enum TraversalError {
+    /// The walk came into the tile at `at` from a side its pipe doesn't connect to.
+    Transition { at: Coord, pipe: Pipe },
+    /// The pipe at `at` leads off the edge of the sketch.
+    OffSketch { at: Coord },
+    /// None of the pipes next to the start lead back around to it, `breaks` is where each
+    /// attempt went wrong.
+    BrokenLoop {
+        start: Coord,
+        breaks: Vec<TraversalError>,
+    },
+}
+
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraversalError::Transition { at, pipe } => write!(
+                f,
+                "the {} pipe at ({}, {}) doesn't connect to the tile before it",
+                *pipe as u8 as char, at.x, at.y
+            ),
+            TraversalError::OffSketch { at } => {
+                write!(f, "the pipe at ({}, {}) leads off the sketch", at.x, at.y)
+            }
+            TraversalError::BrokenLoop { start, breaks } => {
+                write!(f, "no loop through the start at ({}, {})", start.x, start.y)?;
+                for e in breaks {
+                    write!(f, ", {e}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[repr(u8)]
 /// Enumerates the types of pipes in a sketch.
@@ -84,39 +187,47 @@ impl Pipe {
         }
     }
 
-    fn next(&self, prev: Coord, current: Coord) -> Coord {
+    /// The two directions a pipe connects, ground and the start don't connect anywhere.
+    fn ends(&self) -> Option<[Coord; 2]> {
         match *self {
-            Pipe::Vertical => match prev - current {
-                NORTH => current + SOUTH,
-                SOUTH => current + NORTH,
-                _ => panic!("transition not defined"),
-            },
-            Pipe::Horizontal => match prev - current {
-                EAST => current + WEST,
-                WEST => current + EAST,
-                _ => panic!("transition not defined"),
-            },
-            Pipe::NE90 => match prev - current {
-                NORTH => current + EAST,
-                EAST => current + NORTH,
-                _ => panic!("transition not defined"),
-            },
-            Pipe::NW90 => match prev - current {
-                NORTH => current + WEST,
-                WEST => current + NORTH,
-                _ => panic!("transition not defined"),
-            },
-            Pipe::SW90 => match prev - current {
-                SOUTH => current + WEST,
-                WEST => current + SOUTH,
-                _ => panic!("transition not defined"),
-            },
-            Pipe::SE90 => match prev - current {
-                SOUTH => current + EAST,
-                EAST => current + SOUTH,
-                _ => panic!("transition not defined"),
-            },
-            _ => panic!("transition not defined"),
+            Pipe::Vertical => Some([NORTH, SOUTH]),
+            Pipe::Horizontal => Some([EAST, WEST]),
+            Pipe::NE90 => Some([NORTH, EAST]),
+            Pipe::NW90 => Some([NORTH, WEST]),
+            Pipe::SW90 => Some([SOUTH, WEST]),
+            Pipe::SE90 => Some([SOUTH, EAST]),
+            Pipe::Ground | Pipe::Start => None,
+        }
+    }
+
+    fn connects(&self, direction: Coord) -> bool {
+        self.ends().is_some_and(|ends| ends.contains(&direction))
+    }
+
+    /// The pipe that connects two directions.
+    fn connecting(a: Coord, b: Coord) -> Option<Pipe> {
+        [
+            Pipe::Vertical,
+            Pipe::Horizontal,
+            Pipe::NE90,
+            Pipe::NW90,
+            Pipe::SW90,
+            Pipe::SE90,
+        ]
+        .into_iter()
+        .find(|pipe| a != b && pipe.connects(a) && pipe.connects(b))
+    }
+
+    /// Where the walk goes after coming into this pipe at `current` from `prev`.
+    fn next(&self, prev: Coord, current: Coord) -> Result<Coord, TraversalError> {
+        // leave through whichever end the walk didn't come in by
+        match self.ends() {
+            Some([a, b]) if prev - current == a => Ok(current + b),
+            Some([a, b]) if prev - current == b => Ok(current + a),
+            _ => Err(TraversalError::Transition {
+                at: current,
+                pipe: *self,
+            }),
         }
     }
 }
@@ -146,6 +257,111 @@ impl Sketch<Pipe> {
             _ => self.pipe[self.pipe.len() - 2],
         }
     }
+
+    /// Follow the pipe from the start until it comes back around.
+    ///
+    /// Every pipe next to the start that connects to it is tried in turn, and the first one that
+    /// leads back to the start is kept.
+    fn trace(&mut self) -> Result<(), TraversalError> {
+        let mut breaks = Vec::new();
+        for direction in [NORTH, SOUTH, EAST, WEST] {
+            let pos = self.start() + direction;
+            if !self.contains(pos) || !self.get(pos).connects(Coord::default() - direction) {
+                continue;
+            }
+
+            self.pipe.truncate(1);
+            self.pipe.push(pos);
+            match self.follow() {
+                Ok(()) => return Ok(()),
+                Err(e) => breaks.push(e),
+            }
+        }
+
+        self.pipe.truncate(1);
+        Err(TraversalError::BrokenLoop {
+            start: self.start(),
+            breaks,
+        })
+    }
+
+    // follow the pipe until we reach the start
+    fn follow(&mut self) -> Result<(), TraversalError> {
+        while self.current_position() != self.start() {
+            let pipe = self.get(self.current_position());
+            let next = pipe.next(self.previous_position(), self.current_position())?;
+            if !self.contains(next) {
+                return Err(TraversalError::OffSketch {
+                    at: self.current_position(),
+                });
+            }
+            self.pipe.push(next);
+        }
+        Ok(())
+    }
+
+    fn contains(&self, coord: Coord) -> bool {
+        coord.y >= 0
+            && (coord.y as usize) < self.sketch.len()
+            && coord.x >= 0
+            && (coord.x as usize) < self.sketch[coord.y as usize].len()
+    }
+
+    /// The shape of the pipe under the start, from the two tiles of the loop either side of it.
+    fn start_pipe(&self) -> Pipe {
+        let first = self.pipe[1] - self.start();
+        let last = self.pipe[self.pipe.len() - 2] - self.start();
+        Pipe::connecting(first, last).expect("the loop doesn't pass straight through the start")
+    }
+
+    /// The pipe on a tile of a traced loop, with the start replaced by its real shape.
+    fn loop_pipe(&self, coord: Coord) -> Pipe {
+        match self.get(coord) {
+            Pipe::Start => self.start_pipe(),
+            pipe => pipe,
+        }
+    }
+
+    /// Whether each tile is on the traced loop, indexed like the sketch.
+    fn on_loop(&self) -> Vec<Vec<bool>> {
+        let mut on_loop: Vec<_> = self
+            .sketch
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        for coord in &self.pipe {
+            on_loop[coord.y as usize][coord.x as usize] = true;
+        }
+        on_loop
+    }
+
+    /// Whether each tile is enclosed by the traced loop, indexed like the sketch.
+    fn inside(&self) -> Vec<Vec<bool>> {
+        let on_loop = self.on_loop();
+        let mut inside: Vec<_> = self
+            .sketch
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        for (y, row) in self.sketch.iter().enumerate() {
+            // scanning along the row, every loop tile with a pipe going north crosses the loop
+            let mut crossings = 0;
+            for x in 0..row.len() {
+                let coord = Coord {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                if on_loop[y][x] {
+                    if self.loop_pipe(coord).connects(NORTH) {
+                        crossings += 1;
+                    }
+                } else {
+                    inside[y][x] = crossings % 2 == 1;
+                }
+            }
+        }
+        inside
+    }
 }
 
 /// parse_sketch returns the sketch and the starting position of the pipe
@@ -174,49 +390,20 @@ fn parse_sketch(input: &str) -> Sketch<Pipe> {
     }
 }
 
-fn part1(input: &str) -> usize {
+fn traced(input: &str) -> Result<Sketch<Pipe>, TraversalError> {
     let mut sketch = parse_sketch(input);
+    sketch.trace()?;
+    Ok(sketch)
+}
 
-    // Check every direction around the starting position and pick the first valid one
-    for direction in vec![NORTH, SOUTH, EAST, WEST] {
-        let pos = sketch.current_position() + direction;
-        match (direction, sketch.get(pos)) {
-            (NORTH, Pipe::Vertical | Pipe::SE90 | Pipe::SW90) => {
-                sketch.pipe.push(pos);
-                break;
-            }
-            (SOUTH, Pipe::Vertical | Pipe::NE90 | Pipe::NW90) => {
-                sketch.pipe.push(pos);
-                break;
-            }
-            (EAST, Pipe::Horizontal | Pipe::SW90 | Pipe::NW90) => {
-                sketch.pipe.push(pos);
-                break;
-            }
-            (WEST, Pipe::Horizontal | Pipe::SE90 | Pipe::NE90) => {
-                sketch.pipe.push(pos);
-                break;
-            }
-            _ => {}
-        }
-    }
-
-    // Check to see if we found a valid connecting pipe.
-    if sketch.current_position() == sketch.start() {
-        panic!("no valid routes from the start")
-    }
-
-    // Follow the pipe until we reach the start.
-    while sketch.current_position() != sketch.start() {
-        let pipe = sketch.get(sketch.current_position());
-        let next = pipe.next(sketch.previous_position(), sketch.current_position());
-        sketch.pipe.push(next);
-    }
-
-    (sketch.pipe.len() - 1) / 2
+fn part1(input: &str) -> Result<usize, TraversalError> {
+    let sketch = traced(input)?;
+    Ok((sketch.pipe.len() - 1) / 2)
 }
 
-fn part2(input: &str) -> usize {
+// not solved yet, so main doesn't print it
+#[allow(dead_code)]
+fn part2(_input: &str) -> usize {
     todo!()
 }
 
@@ -236,7 +423,27 @@ mod tests {
     #[test]
     fn check_part1() {
         let result = part1(INPUT);
-        assert_eq!(result, 8)
+        assert_eq!(result, Ok(8))
+    }
+
+    #[test]
+    fn check_describe_components() {
+        let input = indoc! {"
+            F7.F7
+            SJ.LJ
+            ..F-."
+        };
+        assert_eq!(
+            describe_components(input),
+            indoc! {"
+                chain: 2 tiles from (2, 0) to (3, 0)
+                main loop: 4 tiles from (0, 1) to (1, 2)
+                loop: 4 tiles from (3, 1) to (4, 2)
+                MM.OO
+                MM.OO
+                ..DD.
+            "}
+        );
     }
 
     #[test]
@@ -244,4 +451,59 @@ mod tests {
         let result = part2(INPUT);
         assert_eq!(result, 0)
     }
+
+    #[test]
+    fn check_next() {
+        let at = Coord { x: 3, y: 3 };
+        assert_eq!(Pipe::SW90.next(at + SOUTH, at), Ok(at + WEST));
+        assert_eq!(
+            Pipe::Vertical.next(at + EAST, at),
+            Err(TraversalError::Transition {
+                at,
+                pipe: Pipe::Vertical
+            })
+        );
+        assert!(Pipe::Ground.next(at + EAST, at).is_err());
+    }
+
+    #[test]
+    fn check_start_directions() {
+        // the pipe north of the start connects to it but runs off the sketch, so the loop is
+        // found by going south instead
+        let input = indoc! {"
+            .|...
+            .S-7.
+            .|.|.
+            .L-J."
+        };
+        assert_eq!(part1(input), Ok(4));
+    }
+
+    #[test]
+    fn check_broken_loop() {
+        let input = indoc! {"
+            S-7
+            |.|
+            L-."
+        };
+        // going south and going east both run into the ground in the bottom right corner
+        let broken = || TraversalError::Transition {
+            at: Coord { x: 2, y: 0 },
+            pipe: Pipe::Ground,
+        };
+        let error = part1(input).unwrap_err();
+        assert_eq!(
+            error,
+            TraversalError::BrokenLoop {
+                start: Coord { x: 0, y: 2 },
+                breaks: vec![broken(), broken()],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "no loop through the start at (0, 2), \
+             the . pipe at (2, 0) doesn't connect to the tile before it, \
+             the . pipe at (2, 0) doesn't connect to the tile before it"
+        );
+    }
 }
@@ -0,0 +1,202 @@
+use crate::{Coord, Pipe, Sketch};
+
+// how big a tile is in an SVG, in pixels
+const TILE: isize = 10;
+
+/// How to draw the tiles that aren't on the loop.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Style {
+    /// Draw pipes that aren't part of the loop faintly, with ANSI escapes in a terminal.
+    pub dim: bool,
+    /// Replace every tile that isn't on the loop with `I` if it's inside the loop or `O` if not.
+    pub mark: bool,
+}
+
+// the box-drawing character for a pipe on the loop
+fn box_drawing(pipe: Pipe) -> char {
+    match pipe {
+        Pipe::Vertical => '│',
+        Pipe::Horizontal => '─',
+        Pipe::NE90 => '└',
+        Pipe::NW90 => '┘',
+        Pipe::SW90 => '┐',
+        Pipe::SE90 => '┌',
+        Pipe::Ground | Pipe::Start => pipe as u8 as char,
+    }
+}
+
+/// The sketch with its traced loop in box-drawing characters, top row first.
+pub fn render(sketch: &Sketch<Pipe>, style: Style) -> String {
+    let on_loop = sketch.on_loop();
+    let inside = sketch.inside();
+
+    let mut rendered = String::new();
+    for (y, row) in sketch.sketch.iter().enumerate().rev() {
+        for (x, &pipe) in row.iter().enumerate() {
+            let coord = Coord {
+                x: x as isize,
+                y: y as isize,
+            };
+            if on_loop[y][x] {
+                rendered.push(box_drawing(sketch.loop_pipe(coord)));
+            } else if style.mark {
+                rendered.push(if inside[y][x] { 'I' } else { 'O' });
+            } else if style.dim && pipe != Pipe::Ground {
+                rendered.push_str(&format!("\x1b[2m{}\x1b[0m", pipe as u8 as char));
+            } else {
+                rendered.push(pipe as u8 as char);
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+// the centre of a tile in SVG coordinates, which start at the top left
+fn centre(sketch: &Sketch<Pipe>, coord: Coord) -> (isize, isize) {
+    let top = sketch.sketch.len() as isize - 1;
+    (coord.x * TILE + TILE / 2, (top - coord.y) * TILE + TILE / 2)
+}
+
+/// The same rendering as an SVG image.
+///
+/// The loop is a black line through the middle of its tiles and pipes that aren't on it are
+/// grey. With `mark` the tiles inside the loop are shaded, `dim` makes no difference.
+pub fn to_svg(sketch: &Sketch<Pipe>, style: Style) -> String {
+    let on_loop = sketch.on_loop();
+    let inside = sketch.inside();
+    let height = sketch.sketch.len() as isize;
+    let width = sketch.sketch.iter().map(Vec::len).max().unwrap_or(0) as isize;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width * TILE,
+        h = height * TILE
+    );
+    for (y, row) in sketch.sketch.iter().enumerate() {
+        for (x, &pipe) in row.iter().enumerate() {
+            let coord = Coord {
+                x: x as isize,
+                y: y as isize,
+            };
+            let (cx, cy) = centre(sketch, coord);
+            if style.mark && !on_loop[y][x] && inside[y][x] {
+                svg.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{TILE}\" height=\"{TILE}\" fill=\"#cfc\"/>\n",
+                    cx - TILE / 2,
+                    cy - TILE / 2
+                ));
+            }
+            // a pipe off the loop is drawn from each of its ends through the middle of the tile
+            if let (false, Some([a, b])) = (on_loop[y][x], pipe.ends()) {
+                let half = TILE / 2;
+                svg.push_str(&format!(
+                    "  <polyline points=\"{},{} {cx},{cy} {},{}\" fill=\"none\" stroke=\"#bbb\"/>\n",
+                    cx + a.x * half,
+                    cy - a.y * half,
+                    cx + b.x * half,
+                    cy - b.y * half
+                ));
+            }
+        }
+    }
+
+    let points: Vec<_> = sketch
+        .pipe
+        .iter()
+        .map(|&coord| {
+            let (x, y) = centre(sketch, coord);
+            format!("{x},{y}")
+        })
+        .collect();
+    svg.push_str(&format!(
+        "  <polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"2\"/>\n",
+        points.join(" ")
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sketch;
+    use indoc::indoc;
+
+    static INPUT: &str = indoc! {"
+        7-F7-
+        .FJ|7
+        SJLL7
+        |F--J
+        LJ.LJ"
+    };
+
+    #[test]
+    fn check_render() {
+        let mut sketch = parse_sketch(INPUT);
+        sketch.trace().unwrap();
+        assert_eq!(
+            render(&sketch, Style::default()),
+            indoc! {"
+                7-┌┐-
+                .┌┘│7
+                ┌┘L└┐
+                │┌──┘
+                └┘.LJ
+            "}
+        );
+        assert_eq!(
+            render(
+                &sketch,
+                Style {
+                    dim: false,
+                    mark: true
+                }
+            ),
+            indoc! {"
+                OO┌┐O
+                O┌┘│O
+                ┌┘I└┐
+                │┌──┘
+                └┘OOO
+            "}
+        );
+        let dimmed = render(
+            &sketch,
+            Style {
+                dim: true,
+                mark: false,
+            },
+        );
+        assert!(dimmed.starts_with("\x1b[2m7\x1b[0m\x1b[2m-\x1b[0m┌┐"));
+    }
+
+    #[test]
+    fn check_svg() {
+        let mut sketch = parse_sketch(indoc! {"
+            .....
+            .S-7.
+            .|.|.
+            .L-J-
+            ....."
+        });
+        sketch.trace().unwrap();
+        let svg = to_svg(
+            &sketch,
+            Style {
+                dim: false,
+                mark: true,
+            },
+        );
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50\" height=\"50\" viewBox=\"0 0 50 50\">\n"
+        ));
+        // the one tile in the middle is inside, and the stray pipe on the right is grey
+        assert!(svg.contains("<rect x=\"20\" y=\"20\" width=\"10\" height=\"10\" fill=\"#cfc\"/>"));
+        assert!(
+            svg.contains("<polyline points=\"50,35 45,35 40,35\" fill=\"none\" stroke=\"#bbb\"/>")
+        );
+        assert!(svg.ends_with("fill=\"none\" stroke=\"black\" stroke-width=\"2\"/>\n</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 1);
+    }
+}